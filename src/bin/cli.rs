@@ -0,0 +1,77 @@
+use std::env;
+use std::io::{self, Read};
+use std::process;
+
+use rubiks_solver::model::coord_cube::CoordCube;
+use rubiks_solver::model::rotation::{format_sequence, parse_sequence};
+use rubiks_solver::model::rubiks_cube::RubiksCube;
+
+// Two-phase solutions this long are found within milliseconds once the tables are built.
+const MAX_SOLUTION_LENGTH: usize = 24;
+
+const USAGE: &str = "Usage:
+    cli solve <facelets>
+    cli solve -
+    cli scramble <n>
+    cli apply <facelets> <moves>
+
+<facelets> is the abbreviation of every sticker, as printed by scramble and apply: each face in
+the order w r b o g y, one row after another. Whitespace is ignored.
+<moves> are rotations such as \"w r' b2\", separated by whitespace or commas.
+solve - reads <moves> from stdin, scrambles a solved cube with them and prints a solution.";
+
+fn parse_cube(facelets: &str) -> Result<RubiksCube<'static>, String> {
+    RubiksCube::from_facelet_string(facelets).map_err(|err| format!("Invalid facelets: {}", err))
+}
+
+fn solve(cube: &RubiksCube) -> Result<String, String> {
+    let solution = CoordCube::from_cube(cube)
+        .solve_two_phase(MAX_SOLUTION_LENGTH)
+        .ok_or("The cube could not be solved")?;
+    Ok(format_sequence(&solution))
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    match args[..] {
+        ["solve", "-"] => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input).map_err(|err| format!("Could not read stdin: {}", err))?;
+            let rotations = parse_sequence(&input).map_err(|err| format!("Invalid moves: {}", err))?;
+
+            let mut cube = RubiksCube::solved();
+            rotations.iter().for_each(|rotation| cube.turn(rotation));
+            println!("{}", solve(&cube)?);
+        },
+        ["solve", facelets] => {
+            let cube = parse_cube(facelets)?;
+            println!("{}", solve(&cube)?);
+        },
+        ["scramble", n] => {
+            let n = n.parse().map_err(|_| format!("Invalid number of moves: {}", n))?;
+            let mut cube = RubiksCube::solved();
+            let rotations = cube.scramble(&mut rand::thread_rng(), n);
+            println!("{}", format_sequence(&rotations));
+            println!("{}", cube.to_facelet_string());
+        },
+        ["apply", facelets, ref moves @ ..] if !moves.is_empty() => {
+            let mut cube = parse_cube(facelets)?;
+            let rotations = parse_sequence(&moves.join(" ")).map_err(|err| format!("Invalid moves: {}", err))?;
+            rotations.iter().for_each(|rotation| cube.turn(rotation));
+            println!("{}", cube.to_facelet_string());
+            println!("{}", cube);
+        },
+        _ => return Err(USAGE.to_string()),
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(message) = run(&args) {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
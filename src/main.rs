@@ -1,34 +1,60 @@
-pub mod model;
+use std::time::Instant;
 
-use model::rubiks_cube::RubiksCube;
+use rubiks_solver::model::coord_cube::CoordCube;
+use rubiks_solver::model::rotation::format_sequence;
+use rubiks_solver::model::rubiks_cube::RubiksCube;
 
+// Scramble depths used by bench_solve. The BFS solver grows by a factor of 12 per depth, so keep
+// these small.
+const SCRAMBLE_DEPTHS: [usize; 4] = [1, 2, 3, 4];
 
-fn main() {
-    let mut solved = RubiksCube::solved();
-    println!("Initial");
-    println!("{}", solved);
-    println!("isSolved {}", solved.is_solved());
-    println!();
+// The number of random turns the demo scrambles with, enough to reach a typical state.
+const DEMO_SCRAMBLE_LENGTH: usize = 25;
+// The longest solution the demo asks the two-phase solver for, which it finds within milliseconds.
+const DEMO_MAX_DEPTH: usize = 24;
 
+/// Scrambles a fresh cube to each depth in SCRAMBLE_DEPTHS and reports how long solve took, the
+/// number of states it expanded and the length of the solution it found.
+fn bench_solve() {
     let mut rng = rand::thread_rng();
-    let rotations = solved.scramble(&mut rng, 5);
 
-    for rotation in rotations {
-        println!("Turned {}", rotation);
+    for depth in SCRAMBLE_DEPTHS {
+        let mut cube = RubiksCube::solved();
+        cube.scramble(&mut rng, depth);
+
+        let report = cube.solve_reported().expect("Every scramble can be solved");
+        println!(
+            "Depth {}: solved in {:?} expanding {} states with {} moves",
+            depth, report.elapsed, report.nodes_expanded, report.solution.len()
+        );
     }
+}
 
+/// Scrambles a cube, solves it with the two-phase solver and checks that the solution works,
+/// printing each step.
+fn demo() {
+    let mut cube = RubiksCube::solved();
+    let scramble = cube.scramble(&mut rand::thread_rng(), DEMO_SCRAMBLE_LENGTH);
+    println!("Scramble: {}", format_sequence(&scramble));
+    println!("{}", cube);
+
+    let start = Instant::now();
+    let solution = CoordCube::from_cube(&cube)
+        .solve_two_phase(DEMO_MAX_DEPTH)
+        .expect("Every state has a solution of at most 24 moves");
+    println!("Solved in {:?}, including building the lookup tables", start.elapsed());
+    println!("Solution ({} moves): {}", solution.len(), format_sequence(&solution));
     println!();
-    println!("After Scramble");
-    println!("{}", solved);
-    println!("isSolved {}", solved.is_solved());
-
 
-    let solve = solved.solve();
+    solution.iter().for_each(|rotation| cube.turn(rotation));
+    println!("{}", cube);
+    assert!(cube.is_solved(), "The solution must solve the scramble");
+}
 
-    print!("Solution was: ");
-    for rotation in solve {
-        print!("{},", rotation);
+/// Runs the demo, or with --bench the solve benchmark.
+fn main() {
+    match std::env::args().nth(1).as_deref() {
+        Some("--bench") => bench_solve(),
+        _ => demo(),
     }
-    println!();
 }
-
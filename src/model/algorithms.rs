@@ -0,0 +1,62 @@
+//! Commonly used named sequences of rotations.
+//!
+//! Algorithms are written in the usual U/R/F/D/L/B notation and parsed with Notation::Standard, so
+//! for example R U R' U' turns red and white, as "r w r' w'".
+
+use super::rotation::{parse_sequence_with, Notation, Rotation};
+
+fn parse(notation: &str) -> Vec<Rotation> {
+    parse_sequence_with(notation, Notation::Standard).expect("built in algorithms are valid notation")
+}
+
+/// R U R' U'
+pub fn sexy_move() -> Vec<Rotation> {
+    parse("R U R' U'")
+}
+
+/// R U R' U R U2 R', orients three last layer corners counter clockwise.
+pub fn sune() -> Vec<Rotation> {
+    parse("R U R' U R U2 R'")
+}
+
+/// R U2 R' U' R U' R', the inverse of sune.
+pub fn anti_sune() -> Vec<Rotation> {
+    parse("R U2 R' U' R U' R'")
+}
+
+/// R U R' U' R' F R2 U' R' U' R U R' F', swaps the UL and UR edges and the UFR and UBR corners.
+pub fn t_perm() -> Vec<Rotation> {
+    parse("R U R' U' R' F R2 U' R' U' R U R' F'")
+}
+
+/// R U R' F' R U R' U' R' F R2 U' R' U', swaps the UF and UR edges and the UFR and UBR corners.
+pub fn jb_perm() -> Vec<Rotation> {
+    parse("R U R' F' R U R' U' R' F R2 U' R' U'")
+}
+
+/// F R U' R' U' R U R' F' R U R' U' R' F R F', swaps the UL and UB edges and the UFR and UBL
+/// corners.
+pub fn y_perm() -> Vec<Rotation> {
+    parse("F R U' R' U' R U R' F' R U R' U' R' F R F'")
+}
+
+/// R U' R U R U R U' R' U' R2, cycles the UF, UL and UR edges.
+pub fn ua_perm() -> Vec<Rotation> {
+    parse("R U' R U R U R U' R' U' R2")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::rubiks_cube::RubiksCube;
+
+    #[test]
+    fn t_perm_twice_is_identity() {
+        let mut cube = RubiksCube::solved();
+        t_perm().iter().for_each(|rotation| cube.turn(rotation));
+        assert!(!cube.is_solved());
+
+        t_perm().iter().for_each(|rotation| cube.turn(rotation));
+        assert!(cube.is_solved());
+    }
+}
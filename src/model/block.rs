@@ -1,8 +1,11 @@
-use super::color::Color;
+use super::color::{Color, ALL_COLORS};
+
+// White and yellow, red and orange, and blue and green.
+const NUM_AXES: usize = 3;
 
 /// Represents a single face of a Block which has it's own color and a pointer to the face that
 /// color is on.
-#[derive(Clone)]
+#[derive(Hash, PartialEq, Eq, Clone)]
 pub struct BlockFace<'a> {
     pub color: &'a Color,
     pub face: &'a Color,
@@ -10,12 +13,13 @@ pub struct BlockFace<'a> {
 
 impl <'a> BlockFace<'a> {
     pub fn is_solved(&self) -> bool {
-        self.color == self.face
+        // Comparing indices alone avoids comparing the colors' names, and every color has its own.
+        self.color.idx == self.face.idx
     }
 }
 
 /// Represents a single piece of the larger rubiks cube.
-#[derive(Clone)]
+#[derive(Hash, PartialEq, Eq, Clone)]
 pub enum Block<'a> {
     Edge(BlockFace<'a>, BlockFace<'a>),
     Corner(BlockFace<'a>, BlockFace<'a>, BlockFace<'a>)
@@ -24,7 +28,7 @@ pub enum Block<'a> {
 impl <'a> Block<'a> {
     /// Returns an Edge with the 2 specified colors where each color is on the correct face.
     pub fn solved_edge(a: &'a Color, b: &'a Color) -> Self {
-        return Block::Edge(BlockFace { color: a, face: a}, BlockFace { color: b, face: b});
+        Block::Edge(BlockFace { color: a, face: a}, BlockFace { color: b, face: b})
     }
 
     /// Returns a Corner with the 3 specified colors where each color is on the correct face.
@@ -45,20 +49,116 @@ impl <'a> Block<'a> {
         }
     }
 
-    /// Returns the color associated with specified face or None if self does not touch the specified
-    /// face.
-    pub fn get_face(&self, face: &Color) -> Option<&Color> {
-        let colors = match self {
-            Block::Edge(a, b) => vec![a, b] ,
+    /// Returns self's stickers, in the order they were given.
+    pub fn stickers(&self) -> Vec<&BlockFace<'a>> {
+        match self {
+            Block::Edge(a, b) => vec![a, b],
             Block::Corner(a, b, c) => vec![a, b, c]
-        };
+        }
+    }
 
-        for color in colors {
-            if color.face == face {
-                return Some(color.color)
-            }
+    /// Like stickers, but allows changing them.
+    pub fn stickers_mut(&mut self) -> Vec<&mut BlockFace<'a>> {
+        match self {
+            Block::Edge(a, b) => vec![a, b],
+            Block::Corner(a, b, c) => vec![a, b, c]
         }
+    }
+
+    /// Returns the color associated with specified face or None if self does not touch the specified
+    /// face.
+    pub fn get_face(&self, face: &Color) -> Option<&'a Color> {
+        self.stickers().into_iter()
+            .find(|block_face| block_face.face == face)
+            .map(|block_face| block_face.color)
+    }
+
+    /// Returns the face that the sticker of the specified color is on or None if self does not have
+    /// a sticker of that color.
+    pub fn face_of(&self, color: &Color) -> Option<&'a Color> {
+        self.stickers().into_iter()
+            .find(|block_face| block_face.color == color)
+            .map(|block_face| block_face.face)
+    }
+
+    /// Returns the colors of self's stickers, in the order they were given.
+    pub fn colors(&self) -> Vec<&'a Color> {
+        self.stickers().into_iter().map(|block_face| block_face.color).collect()
+    }
+
+    /// Returns the faces self's stickers are on, in the same order as colors.
+    pub fn faces(&self) -> Vec<&'a Color> {
+        self.stickers().into_iter().map(|block_face| block_face.face).collect()
+    }
+
+    /// Returns whether self's reference sticker is on the reference face of the position it's in.
+    /// The reference sticker is the one whose color is on the axis through reference, that is
+    /// reference or its opposite, and the reference face is the position's face on that axis. Edges
+    /// and positions without one use the next axis instead: red and orange after white and yellow,
+    /// blue and green after red and orange, and white and yellow after blue and green. For corners
+    /// this is the usual orientation relative to the axis, and for white it agrees with
+    /// RubiksCube::edge_orientations.
+    pub fn is_oriented(&self, reference: &Color) -> bool {
+        let sticker = axis_reference(&self.colors(), reference);
+        self.face_of(sticker) == Some(axis_reference(&self.faces(), reference))
+    }
+}
+
+/// Returns the first of colors on the axis through reference, or on the next axis as described in
+/// Block::is_oriented if none is.
+fn axis_reference<'a>(colors: &[&'a Color], reference: &Color) -> &'a Color {
+    let next = ALL_COLORS[(reference.idx.min(reference.opposite().idx) + 1) % NUM_AXES];
+    [reference, next].into_iter()
+        .find_map(|axis| colors.iter().find(|color| **color == axis || **color == axis.opposite()))
+        .expect("Every block has stickers on two of the three axes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::color::{WHITE, RED, BLUE, YELLOW};
+
+    #[test]
+    fn edges_have_2_stickers_and_corners_3() {
+        let edge = Block::solved_edge(&WHITE, &RED);
+        assert_eq!(edge.stickers().len(), 2);
+        assert_eq!(edge.colors(), vec![&WHITE, &RED]);
+        assert_eq!(edge.faces(), vec![&WHITE, &RED]);
+
+        let corner = Block::Corner(
+            BlockFace { color: &WHITE, face: &RED },
+            BlockFace { color: &RED, face: &BLUE },
+            BlockFace { color: &BLUE, face: &WHITE },
+        );
+        assert_eq!(corner.stickers().len(), 3);
+        assert_eq!(corner.colors(), vec![&WHITE, &RED, &BLUE]);
+        assert_eq!(corner.faces(), vec![&RED, &BLUE, &WHITE]);
+    }
+
+    #[test]
+    fn is_oriented_ignores_position() {
+        let misplaced = Block::Edge(BlockFace { color: &WHITE, face: &WHITE }, BlockFace { color: &BLUE, face: &RED });
+        assert!(!misplaced.is_solved());
+        assert!(misplaced.is_oriented(&WHITE));
+
+        let twisted = Block::Corner(
+            BlockFace { color: &WHITE, face: &RED },
+            BlockFace { color: &RED, face: &BLUE },
+            BlockFace { color: &BLUE, face: &WHITE },
+        );
+        assert!(!twisted.is_oriented(&WHITE));
+        assert!(!twisted.is_oriented(&YELLOW));
+    }
+
+    #[test]
+    fn is_oriented_uses_the_next_axis_for_middle_edges() {
+        assert!(Block::solved_edge(&RED, &BLUE).is_oriented(&WHITE));
+
+        let flipped = Block::Edge(BlockFace { color: &RED, face: &BLUE }, BlockFace { color: &BLUE, face: &RED });
+        assert!(!flipped.is_oriented(&WHITE));
 
-        None
+        // In a middle position the white sticker belongs on the red or orange face.
+        let moved_down = Block::Edge(BlockFace { color: &WHITE, face: &RED }, BlockFace { color: &BLUE, face: &BLUE });
+        assert!(moved_down.is_oriented(&WHITE));
     }
 }
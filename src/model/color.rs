@@ -13,3 +13,109 @@ pub const GREEN: Color = Color { idx: 4, name: "Green", abrv: "g" };
 pub const YELLOW: Color = Color { idx: 5, name: "Yellow", abrv: "y" };
 pub const NUM_COLORS: usize = 6;
 pub const ALL_COLORS: [&Color; NUM_COLORS] = [&WHITE, &RED, &BLUE, &ORANGE, &GREEN, &YELLOW];
+pub const NUM_NEIGHBORS: usize = 4;
+
+// Stored in the order Top, Right, Bottom, Left. 
+const ADJACENT_COLORS: [[&Color; NUM_NEIGHBORS]; NUM_COLORS] = [
+    [&GREEN, &ORANGE, &BLUE, &RED], // White
+    [&WHITE, &BLUE, &YELLOW, &GREEN], // Red
+    [&WHITE, &ORANGE, &YELLOW, &RED], // Blue
+    [&WHITE, &GREEN, &YELLOW, &BLUE], // Orange
+    [&WHITE, &RED, &YELLOW, &ORANGE], // Green
+    [&BLUE, &ORANGE, &GREEN, &RED] // Yellow
+];
+
+// Indexed the same as ALL_COLORS.
+const OPPOSITE_COLORS: [&Color; NUM_COLORS] = [&YELLOW, &ORANGE, &GREEN, &RED, &BLUE, &WHITE];
+
+impl Color {
+    /// Returns the four colors whose faces border this one, in the order Top, Right, Bottom, Left
+    /// as seen when looking directly at this face. That is clockwise from the outside.
+    pub fn adjacent(&self) -> [&'static Color; NUM_NEIGHBORS] {
+        ADJACENT_COLORS[self.idx]
+    }
+
+    /// Returns the color of the face across the cube from this one.
+    pub fn opposite(&self) -> &'static Color {
+        OPPOSITE_COLORS[self.idx]
+    }
+
+    /// Returns the color with the specified abbreviation or None if there isn't one.
+    pub fn from_abrv(abrv: &str) -> Option<&'static Color> {
+        ALL_COLORS.into_iter().find(|color| color.abrv == abrv)
+    }
+}
+
+/// The color of the stickers on each face. The cube is always modeled with the Western scheme
+/// above, naming each face by the color it has there, so a scheme only changes which colors are
+/// shown.
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct ColorScheme {
+    // The color shown on each face, indexed the same as ALL_COLORS.
+    colors: [&'static Color; NUM_COLORS],
+}
+
+/// White opposite yellow, blue opposite green and red opposite orange.
+pub const WESTERN: ColorScheme = ColorScheme { colors: ALL_COLORS };
+/// The Western scheme with blue and yellow swapped, so white is opposite blue.
+pub const JAPANESE: ColorScheme = ColorScheme { colors: [&WHITE, &RED, &YELLOW, &ORANGE, &GREEN, &BLUE] };
+
+impl ColorScheme {
+    /// Returns the scheme that shows colors[i] on the face ALL_COLORS[i] names, or None if colors
+    /// does not contain every color exactly once.
+    pub fn new(colors: [&'static Color; NUM_COLORS]) -> Option<Self> {
+        ALL_COLORS.iter()
+            .all(|color| colors.contains(color))
+            .then_some(ColorScheme { colors })
+    }
+
+    /// Returns the color shown on face.
+    pub fn color_of(&self, face: &Color) -> &'static Color {
+        self.colors[face.idx]
+    }
+
+    /// Returns the face that shows color.
+    pub fn face_of(&self, color: &Color) -> &'static Color {
+        let idx = self.colors.iter()
+            .position(|shown| *shown == color)
+            .expect("Every scheme shows every color");
+        ALL_COLORS[idx]
+    }
+
+    /// Like Color::adjacent, returns the four colors bordering color in this scheme.
+    pub fn adjacent(&self, color: &Color) -> [&'static Color; NUM_NEIGHBORS] {
+        self.face_of(color).adjacent().map(|face| self.color_of(face))
+    }
+
+    /// Returns the color across the cube from color in this scheme.
+    pub fn opposite(&self, color: &Color) -> &'static Color {
+        self.color_of(self.face_of(color).opposite())
+    }
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        WESTERN
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opposite_colors_are_never_adjacent() {
+        for color in ALL_COLORS {
+            assert!(!color.adjacent().contains(&color.opposite()));
+            assert!(!color.adjacent().contains(&color));
+        }
+    }
+
+    #[test]
+    fn opposite_is_an_involution() {
+        for color in ALL_COLORS {
+            assert_ne!(color.opposite(), color);
+            assert_eq!(color.opposite().opposite(), color);
+        }
+    }
+}
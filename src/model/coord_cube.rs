@@ -0,0 +1,401 @@
+//! A representation of the cube as a handful of integers, one per coordinate, which is what fast
+//! solvers search over. Each coordinate numbers the possible values of one property of the cube:
+//!
+//! * twist: the orientations of the corners, 3^7 values since the last is determined by the rest.
+//! * flip: the orientations of the edges, 2^11 values.
+//! * corner_permutation: the position of every corner, 8! values.
+//! * slice_edges, white_edges and yellow_edges: the positions, and order among them, of the four
+//!   edges without white or yellow, the four with white and the four with yellow, 12!/8! values
+//!   each. The three together give the position of every edge.
+//!
+//! Orientations and permutations are those of RubiksCube. Turns are applied through move tables
+//! that give the value each coordinate takes after each move. The tables are built on first use
+//! from the move's effect on a RubiksCube, so the two models always agree.
+
+use std::sync::OnceLock;
+
+use super::color::{ALL_COLORS, NUM_COLORS};
+use super::rotation::{Direction, Rotation};
+use super::move_table::build_move_table;
+use super::rubiks_cube::RubiksCube;
+
+const NUM_CORNERS: usize = 8;
+const NUM_EDGES: usize = 12;
+/// Every face turned clockwise, counterclockwise and twice.
+pub const NUM_MOVES: usize = NUM_COLORS * 3;
+
+pub const TWIST_SIZE: usize = 2187; // 3^7
+pub const FLIP_SIZE: usize = 2048; // 2^11
+pub const CORNER_PERMUTATION_SIZE: usize = 40320; // 8!
+// The positions of four edges, C(12, 4), times the orders they can be in, 4!.
+pub const EDGE_GROUP_SIZE: usize = 11880;
+const EDGE_GROUP_ORDERS: usize = 24;
+
+// The edges tracked by slice_edges, white_edges and yellow_edges, numbered as in
+// RubiksCube::edge_permutation.
+const SLICE_EDGES: [u8; 4] = [8, 9, 10, 11];
+const WHITE_EDGES: [u8; 4] = [0, 1, 2, 3];
+const YELLOW_EDGES: [u8; 4] = [4, 5, 6, 7];
+
+static MOVE_TABLES: OnceLock<MoveTables> = OnceLock::new();
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct CoordCube {
+    pub twist: u16,
+    pub flip: u16,
+    pub corner_permutation: u16,
+    pub slice_edges: u16,
+    pub white_edges: u16,
+    pub yellow_edges: u16,
+}
+
+/// The cube as the arrays RubiksCube reports, which are easy to combine with a move.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Pieces {
+    pub(crate) corner_permutation: [u8; NUM_CORNERS],
+    pub(crate) corner_orientations: [u8; NUM_CORNERS],
+    pub(crate) edge_permutation: [u8; NUM_EDGES],
+    pub(crate) edge_orientations: [u8; NUM_EDGES],
+}
+
+impl Pieces {
+    pub(crate) fn solved() -> Self {
+        Pieces::from_cube(&RubiksCube::solved())
+    }
+
+    pub(crate) fn from_cube(cube: &RubiksCube) -> Self {
+        Pieces {
+            corner_permutation: cube.corner_permutation(),
+            corner_orientations: cube.corner_orientations(),
+            edge_permutation: cube.edge_permutation(),
+            edge_orientations: cube.edge_orientations(),
+        }
+    }
+
+    pub(crate) fn to_cube(&self) -> RubiksCube<'static> {
+        RubiksCube::from_pieces(
+            &self.corner_permutation,
+            &self.corner_orientations,
+            &self.edge_permutation,
+            &self.edge_orientations,
+        )
+    }
+
+    /// Returns the state reached by applying the move other is the state of to self. Positions
+    /// are listed with the piece in them, so the piece other moves into position i is whatever
+    /// self has at other's piece there, and the orientations add up.
+    pub(crate) fn then(&self, other: &Pieces) -> Pieces {
+        let mut result = self.clone();
+        for i in 0..NUM_CORNERS {
+            let from = other.corner_permutation[i] as usize;
+            result.corner_permutation[i] = self.corner_permutation[from];
+            result.corner_orientations[i] = (self.corner_orientations[from] + other.corner_orientations[i]) % 3;
+        }
+        for i in 0..NUM_EDGES {
+            let from = other.edge_permutation[i] as usize;
+            result.edge_permutation[i] = self.edge_permutation[from];
+            result.edge_orientations[i] = (self.edge_orientations[from] + other.edge_orientations[i]) % 2;
+        }
+        result
+    }
+}
+
+/// Returns every face turn in the order the move tables list them: clockwise, counterclockwise
+/// and double for each color in the order of ALL_COLORS.
+pub fn moves() -> Vec<Rotation> {
+    ALL_COLORS.iter()
+        .flat_map(|face| [Direction::Clockwise, Direction::CounterClockwise, Direction::Double]
+            .map(|direction| Rotation { face, direction }))
+        .collect()
+}
+
+/// Returns the index of rotation in moves().
+pub fn move_index(rotation: &Rotation) -> usize {
+    rotation.face.idx * 3 + rotation.direction.clone() as usize
+}
+
+/// Returns the effect of each move in moves() on a solved cube.
+pub(crate) fn move_pieces() -> Vec<Pieces> {
+    moves().iter()
+        .map(|rotation| {
+            let mut cube = RubiksCube::solved();
+            cube.apply(rotation);
+            Pieces::from_cube(&cube)
+        })
+        .collect()
+}
+
+/// Returns the position of perm, a list of distinct values, among all orderings of the same values
+/// in lexicographic order. The values in ascending order are 0.
+pub(crate) fn permutation_index(perm: &[u8]) -> usize {
+    perm.iter()
+        .enumerate()
+        .fold(0, |acc, (i, value)| {
+            acc * (perm.len() - i) + perm[i + 1..].iter().filter(|other| *other < value).count()
+        })
+}
+
+/// The inverse of permutation_index: returns the ordering of values, which must be ascending, at
+/// idx.
+pub(crate) fn nth_permutation(mut idx: usize, values: &[u8]) -> Vec<u8> {
+    let mut digits = vec![0; values.len()];
+    for (i, digit) in digits.iter_mut().enumerate().rev() {
+        *digit = idx % (values.len() - i);
+        idx /= values.len() - i;
+    }
+
+    let mut remaining = values.to_vec();
+    digits.into_iter().map(|digit| remaining.remove(digit)).collect()
+}
+
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1, |acc, i| acc * (n - i) / (i + 1))
+}
+
+/// Returns the position of positions, which must be ascending, among all sets of as many
+/// positions, using the combinatorial number system.
+fn combination_index(positions: &[usize]) -> usize {
+    positions.iter()
+        .enumerate()
+        .map(|(i, position)| binomial(*position, i + 1))
+        .sum()
+}
+
+/// The inverse of combination_index: returns the k ascending positions at idx.
+fn nth_combination(mut idx: usize, k: usize) -> Vec<usize> {
+    let mut result = vec![0; k];
+    for i in (0..k).rev() {
+        let mut position = i;
+        while binomial(position + 1, i + 1) <= idx {
+            position += 1;
+        }
+        idx -= binomial(position, i + 1);
+        result[i] = position;
+    }
+    result
+}
+
+fn twist(pieces: &Pieces) -> usize {
+    pieces.corner_orientations[..NUM_CORNERS - 1].iter()
+        .fold(0, |acc, orientation| acc * 3 + *orientation as usize)
+}
+
+fn set_twist(pieces: &mut Pieces, mut twist: usize) {
+    let mut sum = 0;
+    for orientation in pieces.corner_orientations[..NUM_CORNERS - 1].iter_mut().rev() {
+        *orientation = (twist % 3) as u8;
+        sum += *orientation;
+        twist /= 3;
+    }
+    pieces.corner_orientations[NUM_CORNERS - 1] = (3 - sum % 3) % 3;
+}
+
+fn flip(pieces: &Pieces) -> usize {
+    pieces.edge_orientations[..NUM_EDGES - 1].iter()
+        .fold(0, |acc, orientation| acc * 2 + *orientation as usize)
+}
+
+fn set_flip(pieces: &mut Pieces, mut flip: usize) {
+    let mut sum = 0;
+    for orientation in pieces.edge_orientations[..NUM_EDGES - 1].iter_mut().rev() {
+        *orientation = (flip % 2) as u8;
+        sum += *orientation;
+        flip /= 2;
+    }
+    pieces.edge_orientations[NUM_EDGES - 1] = sum % 2;
+}
+
+fn corner_permutation(pieces: &Pieces) -> usize {
+    permutation_index(&pieces.corner_permutation)
+}
+
+fn set_corner_permutation(pieces: &mut Pieces, idx: usize) {
+    let corners: Vec<u8> = (0..NUM_CORNERS as u8).collect();
+    pieces.corner_permutation.copy_from_slice(&nth_permutation(idx, &corners));
+}
+
+/// Returns which positions hold the edges in group and in what order, as a number below
+/// EDGE_GROUP_SIZE.
+fn edge_group(pieces: &Pieces, group: &[u8; 4]) -> usize {
+    let (positions, edges): (Vec<usize>, Vec<u8>) = pieces.edge_permutation.iter()
+        .enumerate()
+        .filter(|(_, edge)| group.contains(edge))
+        .map(|(position, edge)| (position, *edge))
+        .unzip();
+    combination_index(&positions) * EDGE_GROUP_ORDERS + permutation_index(&edges)
+}
+
+/// Puts the edges in group where edge_group says they are for idx, and the rest of the edges in
+/// the remaining positions in ascending order.
+fn set_edge_group(pieces: &mut Pieces, group: &[u8; 4], idx: usize) {
+    let positions = nth_combination(idx / EDGE_GROUP_ORDERS, group.len());
+    let mut edges = nth_permutation(idx % EDGE_GROUP_ORDERS, group).into_iter();
+    let mut others = (0..NUM_EDGES as u8).filter(|edge| !group.contains(edge));
+
+    for (position, edge) in pieces.edge_permutation.iter_mut().enumerate() {
+        *edge = match positions.contains(&position) {
+            true => edges.next().unwrap(),
+            false => others.next().unwrap(),
+        };
+    }
+}
+
+/// Returns the move table of a coordinate over moves(). get reads the coordinate from a state and
+/// set changes a state to have the specified value.
+pub(crate) fn build_table(size: usize, get: impl Fn(&Pieces) -> usize, set: impl Fn(&mut Pieces, usize)) -> Vec<Vec<u16>> {
+    let solved = Pieces::solved();
+    let decode = |idx| {
+        let mut pieces = solved.clone();
+        set(&mut pieces, idx);
+        pieces
+    };
+    build_move_table(size, decode, get, &move_pieces(), Pieces::then)
+}
+
+/// The move table of each coordinate, as build_table returns them.
+pub(crate) struct MoveTables {
+    pub(crate) twist: Vec<Vec<u16>>,
+    pub(crate) flip: Vec<Vec<u16>>,
+    pub(crate) corner_permutation: Vec<Vec<u16>>,
+    pub(crate) slice_edges: Vec<Vec<u16>>,
+    pub(crate) white_edges: Vec<Vec<u16>>,
+    pub(crate) yellow_edges: Vec<Vec<u16>>,
+}
+
+/// Returns the move tables, building them if this is the first use.
+pub(crate) fn move_tables() -> &'static MoveTables {
+    MOVE_TABLES.get_or_init(build_move_tables)
+}
+
+fn build_move_tables() -> MoveTables {
+    let edge_group_table = |group: &'static [u8; 4]| build_table(
+        EDGE_GROUP_SIZE,
+        |pieces| edge_group(pieces, group),
+        |pieces, idx| set_edge_group(pieces, group, idx),
+    );
+
+    MoveTables {
+        twist: build_table(TWIST_SIZE, twist, set_twist),
+        flip: build_table(FLIP_SIZE, flip, set_flip),
+        corner_permutation: build_table(CORNER_PERMUTATION_SIZE, corner_permutation, set_corner_permutation),
+        slice_edges: edge_group_table(&SLICE_EDGES),
+        white_edges: edge_group_table(&WHITE_EDGES),
+        yellow_edges: edge_group_table(&YELLOW_EDGES),
+    }
+}
+
+impl CoordCube {
+    pub fn solved() -> Self {
+        CoordCube::from_pieces(&Pieces::solved())
+    }
+
+    pub fn from_cube(cube: &RubiksCube) -> Self {
+        CoordCube::from_pieces(&Pieces::from_cube(cube))
+    }
+
+    fn from_pieces(pieces: &Pieces) -> Self {
+        CoordCube {
+            twist: twist(pieces) as u16,
+            flip: flip(pieces) as u16,
+            corner_permutation: corner_permutation(pieces) as u16,
+            slice_edges: edge_group(pieces, &SLICE_EDGES) as u16,
+            white_edges: edge_group(pieces, &WHITE_EDGES) as u16,
+            yellow_edges: edge_group(pieces, &YELLOW_EDGES) as u16,
+        }
+    }
+
+    /// Returns the RubiksCube in the state self describes, with an empty history.
+    pub fn to_cube(&self) -> RubiksCube<'static> {
+        let mut pieces = Pieces::solved();
+        set_twist(&mut pieces, self.twist as usize);
+        set_flip(&mut pieces, self.flip as usize);
+        set_corner_permutation(&mut pieces, self.corner_permutation as usize);
+        pieces.edge_permutation = self.edge_permutation();
+
+        pieces.to_cube()
+    }
+
+    /// Returns the edge permutation that slice_edges, white_edges and yellow_edges describe
+    /// together, as RubiksCube::edge_permutation lists it.
+    pub(crate) fn edge_permutation(&self) -> [u8; NUM_EDGES] {
+        let mut result = [0; NUM_EDGES];
+        let mut pieces = Pieces::solved();
+
+        // Each group coordinate fixes the positions of its own four edges.
+        let groups = [(&SLICE_EDGES, self.slice_edges), (&WHITE_EDGES, self.white_edges), (&YELLOW_EDGES, self.yellow_edges)];
+        for (group, idx) in groups {
+            set_edge_group(&mut pieces, group, idx as usize);
+            for (position, edge) in pieces.edge_permutation.iter().enumerate() {
+                if group.contains(edge) {
+                    result[position] = *edge;
+                }
+            }
+        }
+
+        result
+    }
+
+    pub fn is_solved(&self) -> bool {
+        *self == CoordCube::solved()
+    }
+
+    /// Applies rotation through the move tables, building them if this is the first use.
+    pub fn apply(&mut self, rotation: &Rotation) {
+        let tables = move_tables();
+        let m = move_index(rotation);
+        let next = |table: &[Vec<u16>], value: u16| table[value as usize][m];
+
+        self.twist = next(&tables.twist, self.twist);
+        self.flip = next(&tables.flip, self.flip);
+        self.corner_permutation = next(&tables.corner_permutation, self.corner_permutation);
+        self.slice_edges = next(&tables.slice_edges, self.slice_edges);
+        self.white_edges = next(&tables.white_edges, self.white_edges);
+        self.yellow_edges = next(&tables.yellow_edges, self.yellow_edges);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_functions_invert_each_other() {
+        let values = [2, 3, 5, 7, 11];
+        for idx in 0..120 {
+            assert_eq!(permutation_index(&nth_permutation(idx, &values)), idx);
+        }
+
+        for idx in 0..binomial(NUM_EDGES, 4) {
+            let positions = nth_combination(idx, 4);
+            assert!(positions.windows(2).all(|pair| pair[0] < pair[1]) && positions[3] < NUM_EDGES);
+            assert_eq!(combination_index(&positions), idx);
+        }
+    }
+
+    #[test]
+    fn converts_to_and_from_rubiks_cubes() {
+        for seed in 0..10 {
+            let mut cube = RubiksCube::solved();
+            cube.scramble_seeded(seed, 30);
+            assert!(CoordCube::from_cube(&cube).to_cube() == cube);
+        }
+    }
+
+    #[test]
+    fn apply_agrees_with_turning_a_rubiks_cube() {
+        let mut cube = RubiksCube::solved();
+        cube.scramble_seeded(599, 30);
+
+        for rotation in moves() {
+            let mut coords = CoordCube::from_cube(&cube);
+            coords.apply(&rotation);
+
+            let mut turned = cube.clone();
+            turned.turn(&rotation);
+            assert_eq!(coords, CoordCube::from_cube(&turned));
+        }
+    }
+}
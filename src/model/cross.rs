@@ -0,0 +1,151 @@
+//! A solver for the cross: the four edges around one face, which most speedsolving methods place
+//! first. The search only tracks those four edges, so it is much faster than solving the cube.
+
+use std::collections::VecDeque;
+
+use super::color::{Color, NUM_COLORS, NUM_NEIGHBORS};
+use super::coord_cube::moves;
+use super::kociemba::is_redundant;
+use super::rotation::Rotation;
+use super::rubiks_cube::{get_color_rotations, RubiksCube};
+
+// Every cross can be solved in this many moves, counting double turns as one.
+const MAX_CROSS_MOVES: usize = 8;
+
+/// The idx of the face each sticker of an edge is on, in the order of the edge's colors.
+type Edge = [u8; 2];
+
+/// How a rotation moves stickers: the idx of the face turned and where it takes each face.
+type Turn = (u8, [Option<&'static Color>; NUM_COLORS]);
+
+fn turn(edge: &Edge, (face, rotations): &Turn) -> Edge {
+    match edge.contains(face) {
+        true => edge.map(|face| rotations[face as usize].unwrap().idx as u8),
+        false => *edge,
+    }
+}
+
+// Every pair of the four cross edges.
+const PAIRS: [(usize, usize); 6] = [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)];
+
+// The number of values key can take for one edge.
+const EDGE_KEYS: usize = NUM_COLORS * NUM_COLORS;
+
+fn key(edges: &[Edge]) -> usize {
+    edges.iter().fold(0, |acc, edge| acc * EDGE_KEYS + edge[0] as usize * NUM_COLORS + edge[1] as usize)
+}
+
+/// Returns the number of moves needed to take a pair of edges from each state, indexed by key, to
+/// homes, ignoring every other piece.
+fn pair_distances(homes: [Edge; 2], turns: &[Turn]) -> Vec<u8> {
+    let mut distances = vec![u8::MAX; EDGE_KEYS * EDGE_KEYS];
+    distances[key(&homes)] = 0;
+    let mut queue = VecDeque::from([homes]);
+
+    while let Some(edges) = queue.pop_front() {
+        let distance = distances[key(&edges)];
+        for t in turns {
+            let next = edges.map(|edge| turn(&edge, t));
+            if distances[key(&next)] == u8::MAX {
+                distances[key(&next)] = distance + 1;
+                queue.push_back(next);
+            }
+        }
+    }
+
+    distances
+}
+
+/// The cross edges being solved and what the search needs to know about them.
+struct Cross {
+    homes: [Edge; NUM_NEIGHBORS],
+    /// The pair_distances of each pair of edges in PAIRS.
+    distances: Vec<Vec<u8>>,
+    turns: Vec<Turn>,
+}
+
+impl Cross {
+    fn estimate(&self, edges: &[Edge; NUM_NEIGHBORS]) -> usize {
+        PAIRS.iter()
+            .zip(self.distances.iter())
+            .map(|((a, b), distances)| distances[key(&[edges[*a], edges[*b]])])
+            .max()
+            .unwrap_or(0) as usize
+    }
+
+    /// Extends path by depth moves that place every edge, returning whether it found them.
+    fn search(&self, edges: [Edge; NUM_NEIGHBORS], depth: usize, path: &mut Vec<usize>) -> bool {
+        if self.estimate(&edges) > depth {
+            return false;
+        }
+        if depth == 0 {
+            return edges == self.homes;
+        }
+
+        for (m, t) in self.turns.iter().enumerate() {
+            if is_redundant(path.last().copied(), m) {
+                continue;
+            }
+
+            path.push(m);
+            if self.search(edges.map(|edge| turn(&edge, t)), depth - 1, path) {
+                return true;
+            }
+            path.pop();
+        }
+
+        false
+    }
+}
+
+impl <'a> RubiksCube<'a> {
+    /// Returns a shortest sequence of rotations that places the four edges around face, counting
+    /// double turns as one move, without regard to the rest of the cube. Returns None if there
+    /// isn't one of at most 8 moves, which only happens if the edges are not all on the cube.
+    pub fn solve_cross(&self, face: &Color) -> Option<Vec<Rotation>> {
+        let all_moves = moves();
+        let turns: Vec<Turn> = all_moves.iter()
+            .map(|rotation| (rotation.face.idx as u8, get_color_rotations(rotation)))
+            .collect();
+
+        let colors = face.adjacent().map(|side| [face, side]);
+        let homes = colors.map(|piece| piece.map(|color| color.idx as u8));
+        let mut edges = homes;
+        for (edge, piece) in edges.iter_mut().zip(colors) {
+            let block = self.find_block(&piece)?;
+            for (sticker, color) in edge.iter_mut().zip(piece) {
+                *sticker = block.face_of(color)?.idx as u8;
+            }
+        }
+
+        let distances = PAIRS.iter().map(|(a, b)| pair_distances([homes[*a], homes[*b]], &turns)).collect();
+        let cross = Cross { homes, distances, turns };
+        let mut path = Vec::new();
+
+        let found = (0..=MAX_CROSS_MOVES).any(|depth| cross.search(edges, depth, &mut path));
+        found.then(|| path.iter().map(|m| all_moves[*m].clone()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::color::{ALL_COLORS, WHITE};
+
+    #[test]
+    fn solve_cross_places_the_four_edges() {
+        for (seed, face) in ALL_COLORS.into_iter().enumerate() {
+            let mut cube = RubiksCube::solved();
+            cube.scramble_seeded(609 + seed as u64, 30);
+
+            let solution = cube.solve_cross(face).unwrap();
+            assert!(solution.len() <= MAX_CROSS_MOVES);
+            solution.iter().for_each(|rotation| cube.turn(rotation));
+            for side in face.adjacent() {
+                assert!(cube.find_block(&[face, side]).is_some_and(|edge| edge.is_solved()));
+            }
+        }
+
+        assert_eq!(RubiksCube::solved().solve_cross(&WHITE), Some(Vec::new()));
+    }
+}
@@ -0,0 +1,237 @@
+//! Reading and writing a cube as a JSON object with one key per face, each mapping to the face's
+//! stickers as a 3×3 array of color abbreviations, e.g.
+//!
+//! ```text
+//! {
+//!   "w": [["w", "w", "w"], ["w", "w", "w"], ["w", "w", "w"]],
+//!   "r": [["r", "r", "r"], ["r", "r", "r"], ["r", "r", "r"]],
+//!   ...
+//! }
+//! ```
+//!
+//! Faces are keyed by the abbreviation of their center's color and laid out as face_colors returns
+//! them. Only this shape is read, so it is parsed by hand rather than with a JSON library.
+
+use std::fmt::{Display, Formatter, Error};
+
+use super::color::{Color, ALL_COLORS, NUM_COLORS};
+use super::rubiks_cube::{FaceletError, RubiksCube, SIDE_LEN};
+
+/// The reasons a string could not be read by RubiksCube::from_json.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum JsonError {
+    /// The input stops being an object of arrays of strings at this byte offset.
+    Syntax(usize),
+    /// A key is not the abbreviation of a face's color.
+    UnknownFace(String),
+    /// The face with this abbreviation appears more than once.
+    DuplicateFace(String),
+    /// The face with this abbreviation is missing.
+    MissingFace(String),
+    /// The face does not have 3 rows, or if row is set, that row does not have 3 cells.
+    WrongShape { face: String, row: Option<usize> },
+    /// The cell at row and col of face is value, which is not a color abbreviation.
+    UnknownColor { face: String, row: usize, col: usize, value: String },
+    /// The stickers could not come from turning a solved cube.
+    Invalid(FaceletError),
+}
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            JsonError::Syntax(offset) => write!(f, "invalid JSON at byte {}", offset),
+            JsonError::UnknownFace(key) => write!(f, "unknown face \"{}\"", key),
+            JsonError::DuplicateFace(face) => write!(f, "face {} appears more than once", face),
+            JsonError::MissingFace(face) => write!(f, "missing face {}", face),
+            JsonError::WrongShape { face, row: None } => write!(f, "face {} does not have {} rows", face, SIDE_LEN),
+            JsonError::WrongShape { face, row: Some(row) } =>
+                write!(f, "row {} of face {} does not have {} cells", row, face, SIDE_LEN),
+            JsonError::UnknownColor { face, row, col, value } =>
+                write!(f, "unknown color \"{}\" at row {}, column {} of face {}", value, row, col, face),
+            JsonError::Invalid(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+type Face = [[&'static Color; SIDE_LEN]; SIDE_LEN];
+
+struct Parser<'s> {
+    input: &'s str,
+    // The byte offset of the next character to read.
+    pos: usize,
+}
+
+impl <'s> Parser<'s> {
+    /// Skips whitespace and returns the next byte without consuming it.
+    fn peek(&mut self) -> Option<u8> {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+        self.input.as_bytes().get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), JsonError> {
+        if self.peek() != Some(byte) {
+            return Err(JsonError::Syntax(self.pos));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// Reads a string, which is not allowed to contain escapes.
+    fn string(&mut self) -> Result<&'s str, JsonError> {
+        self.expect(b'"')?;
+        let rest = &self.input[self.pos..];
+        let len = rest.find(['"', '\\']).ok_or(JsonError::Syntax(self.input.len()))?;
+        if rest[len..].starts_with('\\') {
+            return Err(JsonError::Syntax(self.pos + len));
+        }
+
+        self.pos += len + 1;
+        Ok(&rest[..len])
+    }
+
+    /// Reads a comma separated list between open and close, calling item to read each element.
+    fn list(&mut self, open: u8, close: u8, mut item: impl FnMut(&mut Self) -> Result<(), JsonError>) -> Result<(), JsonError> {
+        self.expect(open)?;
+        if self.peek() == Some(close) {
+            self.pos += 1;
+            return Ok(());
+        }
+
+        loop {
+            item(self)?;
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(byte) if byte == close => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                _ => return Err(JsonError::Syntax(self.pos)),
+            }
+        }
+    }
+
+    /// Reads the 3×3 array of stickers on face.
+    fn face(&mut self, face: &Color) -> Result<Face, JsonError> {
+        let mut rows = Vec::new();
+        self.list(b'[', b']', |parser| {
+            let row = rows.len();
+            let mut cells = Vec::new();
+            parser.list(b'[', b']', |parser| {
+                let value = parser.string()?;
+                let color = Color::from_abrv(value).ok_or_else(|| JsonError::UnknownColor {
+                    face: face.abrv.to_string(),
+                    row,
+                    col: cells.len(),
+                    value: value.to_string(),
+                })?;
+                cells.push(color);
+                Ok(())
+            })?;
+
+            let cells = cells.try_into()
+                .map_err(|_| JsonError::WrongShape { face: face.abrv.to_string(), row: Some(row) })?;
+            rows.push(cells);
+            Ok(())
+        })?;
+
+        rows.try_into().map_err(|_| JsonError::WrongShape { face: face.abrv.to_string(), row: None })
+    }
+}
+
+impl <'a> RubiksCube<'a> {
+    /// Returns the stickers of self in the format from_json reads, with the faces in the order of
+    /// ALL_COLORS. Colors are named as in the Western scheme whatever scheme self shows.
+    pub fn to_json(&self) -> String {
+        let faces: Vec<String> = ALL_COLORS.iter()
+            .map(|face| {
+                let rows: Vec<String> = self.face_colors(face).unwrap().iter()
+                    .map(|row| {
+                        let cells: Vec<String> = row.iter().map(|color| format!("\"{}\"", color.abrv)).collect();
+                        format!("[{}]", cells.join(", "))
+                    })
+                    .collect();
+                format!("  \"{}\": [{}]", face.abrv, rows.join(", "))
+            })
+            .collect();
+
+        format!("{{\n{}\n}}\n", faces.join(",\n"))
+    }
+
+    /// Parses an object with a key for each face, in any order, and builds the cube with
+    /// from_facelets. Errors about a single sticker name its face, row and column.
+    pub fn from_json(json: &str) -> Result<Self, JsonError> {
+        let mut parser = Parser { input: json, pos: 0 };
+        let mut faces: [Option<Face>; NUM_COLORS] = [None; NUM_COLORS];
+
+        parser.list(b'{', b'}', |parser| {
+            let key = parser.string()?;
+            let face = Color::from_abrv(key).ok_or_else(|| JsonError::UnknownFace(key.to_string()))?;
+            parser.expect(b':')?;
+            let stickers = parser.face(face)?;
+            match faces[face.idx].replace(stickers) {
+                Some(_) => Err(JsonError::DuplicateFace(face.abrv.to_string())),
+                None => Ok(()),
+            }
+        })?;
+        if parser.peek().is_some() {
+            return Err(JsonError::Syntax(parser.pos));
+        }
+
+        let mut stickers = [[[ALL_COLORS[0]; SIDE_LEN]; SIDE_LEN]; NUM_COLORS];
+        for (face, color) in stickers.iter_mut().zip(ALL_COLORS) {
+            *face = faces[color.idx].ok_or_else(|| JsonError::MissingFace(color.abrv.to_string()))?;
+        }
+
+        Self::from_facelets(stickers).map_err(JsonError::Invalid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_json_round_trips() {
+        assert!(RubiksCube::from_json(&RubiksCube::solved().to_json()).unwrap() == RubiksCube::solved());
+
+        for seed in 0..10 {
+            let mut cube = RubiksCube::solved();
+            cube.scramble_seeded(seed, 30);
+            assert!(RubiksCube::from_json(&cube.to_json()).unwrap() == cube);
+        }
+    }
+
+    #[test]
+    fn from_json_names_the_bad_sticker() {
+        let solved = RubiksCube::solved().to_json();
+        let error = |json: String| RubiksCube::from_json(&json).err();
+
+        let json = solved.replacen("[\"b\", \"b\", \"b\"]", "[\"b\", \"b\", \"x\"]", 1);
+        let value = "x".to_string();
+        assert_eq!(error(json), Some(JsonError::UnknownColor { face: "b".to_string(), row: 0, col: 2, value }));
+
+        let json = solved.replacen(", [\"o\", \"o\", \"o\"]]", "]", 1);
+        assert_eq!(error(json), Some(JsonError::WrongShape { face: "o".to_string(), row: None }));
+
+        let json = solved.replacen("[\"r\", \"r\", \"r\"]", "[\"r\", \"r\"]", 1);
+        assert_eq!(error(json), Some(JsonError::WrongShape { face: "r".to_string(), row: Some(0) }));
+    }
+
+    #[test]
+    fn from_json_rejects_bad_keys_and_cubes() {
+        let solved = RubiksCube::solved().to_json();
+        let error = |json: String| RubiksCube::from_json(&json).err();
+
+        assert_eq!(error(solved.replacen("\"g\":", "\"x\":", 1)), Some(JsonError::UnknownFace("x".to_string())));
+        assert_eq!(error(solved.replacen("\"g\":", "\"w\":", 1)), Some(JsonError::DuplicateFace("w".to_string())));
+        assert_eq!(error("{}".to_string()), Some(JsonError::MissingFace("w".to_string())));
+        assert_eq!(error(solved.replacen('{', "", 1)), Some(JsonError::Syntax(3)));
+
+        // A green sticker on the white face's corner gives that corner two green stickers.
+        let json = solved.replacen("[[\"w\", \"w\", \"w\"]", "[[\"g\", \"w\", \"w\"]", 1);
+        assert!(matches!(error(json), Some(JsonError::Invalid(_))));
+    }
+}
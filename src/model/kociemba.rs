@@ -0,0 +1,307 @@
+//! Kociemba's two-phase algorithm, which solves the cube in two searches:
+//!
+//! * Phase 1 reaches G1, the states with every corner and edge oriented and the four edges
+//!   without white or yellow between the red, blue, orange and green centers. It searches with
+//!   every move over the twist, flip and the positions of those four edges.
+//! * Phase 2 solves a state in G1 using only the moves that keep it there: any turn of white or
+//!   yellow and double turns of the rest. It searches over the corner permutation and the
+//!   permutations of the white and yellow edges and of the other four.
+//!
+//! Both phases are iterative deepening searches guided by pruning tables, which give the distance
+//! to the phase's goal of each pair of coordinates and so a lower bound on the distance of the
+//! whole state. Phase 1 is tried at increasing depths, and each way it finds of reaching G1 is
+//! handed to phase 2 with whatever moves remain, so the first solution found is usually close to
+//! the shortest.
+
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+
+use super::color::{Color, ALL_COLORS, WHITE, YELLOW};
+use super::coord_cube::{
+    build_table, move_tables, moves, nth_permutation, permutation_index, CoordCube, Pieces,
+    CORNER_PERMUTATION_SIZE, FLIP_SIZE, NUM_MOVES, TWIST_SIZE,
+};
+use super::rotation::{Direction, Rotation};
+
+// The sets of positions that the four edges without white or yellow can be in, C(12, 4).
+const SLICE_POSITIONS: usize = 495;
+// The ways of ordering those four edges, and the coordinate CoordCube::slice_edges combines.
+const SLICE_ORDERS: usize = 24;
+// The orders of the eight white and yellow edges, 8!.
+const LAYER_EDGE_PERMUTATION_SIZE: usize = 40320;
+const NUM_LAYER_EDGES: usize = 8;
+
+static TABLES: OnceLock<Tables> = OnceLock::new();
+
+struct Tables {
+    /// Move tables for the phase 2 edge coordinates, over the same moves as CoordCube's. Only the
+    /// entries of phase 2 moves are meaningful.
+    layer_edges: Vec<Vec<u16>>,
+    slice_permutation: Vec<Vec<u16>>,
+    /// Pruning tables, indexed by the first coordinate times the size of the second plus the
+    /// second.
+    twist_slice: Vec<u8>,
+    flip_slice: Vec<u8>,
+    corner_slice: Vec<u8>,
+    layer_edge_slice: Vec<u8>,
+}
+
+// moves() lists the three turns of each face together, in the order of Direction.
+fn face(m: usize) -> &'static Color {
+    ALL_COLORS[m / 3]
+}
+
+/// Returns whether move m keeps a state in G1.
+fn is_phase_2_move(m: usize) -> bool {
+    face(m) == &WHITE || face(m) == &YELLOW || m % 3 == Direction::Double as usize
+}
+
+/// Returns whether m can be skipped after last: turning the same face twice in a row is one move,
+/// and turns of opposite faces commute, so only one order of them is searched.
+pub(crate) fn is_redundant(last: Option<usize>, m: usize) -> bool {
+    match last {
+        Some(last) => face(last) == face(m)
+            || (face(last).opposite() == face(m) && face(last).idx > face(m).idx),
+        None => false,
+    }
+}
+
+fn layer_edges(pieces: &Pieces) -> usize {
+    permutation_index(&pieces.edge_permutation[..NUM_LAYER_EDGES])
+}
+
+fn set_layer_edges(pieces: &mut Pieces, idx: usize) {
+    let edges: Vec<u8> = (0..NUM_LAYER_EDGES as u8).collect();
+    pieces.edge_permutation[..NUM_LAYER_EDGES].copy_from_slice(&nth_permutation(idx, &edges));
+}
+
+fn slice_permutation(pieces: &Pieces) -> usize {
+    permutation_index(&pieces.edge_permutation[NUM_LAYER_EDGES..])
+}
+
+fn set_slice_permutation(pieces: &mut Pieces, idx: usize) {
+    let edges: Vec<u8> = (NUM_LAYER_EDGES as u8..12).collect();
+    pieces.edge_permutation[NUM_LAYER_EDGES..].copy_from_slice(&nth_permutation(idx, &edges));
+}
+
+/// Builds a pruning table by a breadth first search over pairs of coordinates from goal, using
+/// only allowed_moves. next_a and next_b give the value each coordinate takes after a move. The
+/// allowed moves include the inverse of each one, so distance from the goal is distance to it.
+pub(crate) fn pruning_table(
+    sizes: (usize, usize),
+    goal: (usize, usize),
+    allowed_moves: &[usize],
+    next_a: impl Fn(usize, usize) -> usize,
+    next_b: impl Fn(usize, usize) -> usize,
+) -> Vec<u8> {
+    let mut table = vec![u8::MAX; sizes.0 * sizes.1];
+    table[goal.0 * sizes.1 + goal.1] = 0;
+    let mut queue = VecDeque::from([goal]);
+
+    while let Some((a, b)) = queue.pop_front() {
+        let depth = table[a * sizes.1 + b];
+        for m in allowed_moves {
+            let next = (next_a(a, *m), next_b(b, *m));
+            let idx = next.0 * sizes.1 + next.1;
+            if table[idx] == u8::MAX {
+                table[idx] = depth + 1;
+                queue.push_back(next);
+            }
+        }
+    }
+
+    table
+}
+
+/// Returns the function giving the value reached by applying a move to a value of the coordinate
+/// that table is the move table of.
+pub(crate) fn next(table: &[Vec<u16>]) -> impl Fn(usize, usize) -> usize + '_ {
+    move |value, m| table[value][m] as usize
+}
+
+fn build_tables() -> Tables {
+    let coord_tables = move_tables();
+    let layer_edges = build_table(LAYER_EDGE_PERMUTATION_SIZE, layer_edges, set_layer_edges);
+    let slice_permutation = build_table(SLICE_ORDERS, slice_permutation, set_slice_permutation);
+
+    let all_moves: Vec<usize> = (0..NUM_MOVES).collect();
+    let phase_2_moves: Vec<usize> = all_moves.iter().copied().filter(|m| is_phase_2_move(*m)).collect();
+    let next_slice_position = |position: usize, m: usize| {
+        coord_tables.slice_edges[position * SLICE_ORDERS][m] as usize / SLICE_ORDERS
+    };
+
+    let solved = CoordCube::solved();
+    let solved_slice_position = solved.slice_edges as usize / SLICE_ORDERS;
+    let twist_slice = pruning_table(
+        (TWIST_SIZE, SLICE_POSITIONS),
+        (solved.twist as usize, solved_slice_position),
+        &all_moves,
+        next(&coord_tables.twist),
+        next_slice_position,
+    );
+    let flip_slice = pruning_table(
+        (FLIP_SIZE, SLICE_POSITIONS),
+        (solved.flip as usize, solved_slice_position),
+        &all_moves,
+        next(&coord_tables.flip),
+        next_slice_position,
+    );
+    let corner_slice = pruning_table(
+        (CORNER_PERMUTATION_SIZE, SLICE_ORDERS),
+        (solved.corner_permutation as usize, 0),
+        &phase_2_moves,
+        next(&coord_tables.corner_permutation),
+        next(&slice_permutation),
+    );
+    let layer_edge_slice = pruning_table(
+        (LAYER_EDGE_PERMUTATION_SIZE, SLICE_ORDERS),
+        (0, 0),
+        &phase_2_moves,
+        next(&layer_edges),
+        next(&slice_permutation),
+    );
+
+    Tables { layer_edges, slice_permutation, twist_slice, flip_slice, corner_slice, layer_edge_slice }
+}
+
+/// The state of one search for a solution to start no longer than max_depth moves.
+struct Search<'t> {
+    tables: &'t Tables,
+    start: CoordCube,
+    max_depth: usize,
+    /// The moves of the current path, as indices into moves().
+    path: Vec<usize>,
+}
+
+impl <'t> Search<'t> {
+    /// Extends path by depth moves that take a state with the specified phase 1 coordinates to G1,
+    /// then tries to finish with phase 2.
+    fn phase_1(&mut self, twist: usize, flip: usize, slice_position: usize, depth: usize) -> bool {
+        let estimate = self.tables.twist_slice[twist * SLICE_POSITIONS + slice_position]
+            .max(self.tables.flip_slice[flip * SLICE_POSITIONS + slice_position]);
+        if estimate as usize > depth {
+            return false;
+        }
+        if depth == 0 {
+            // A path ending in a phase 2 move reached G1 one move earlier, and was tried then.
+            return self.path.last().is_none_or(|last| !is_phase_2_move(*last)) && self.start_phase_2();
+        }
+
+        let coord_tables = move_tables();
+        for m in 0..NUM_MOVES {
+            if is_redundant(self.path.last().copied(), m) {
+                continue;
+            }
+
+            self.path.push(m);
+            let found = self.phase_1(
+                coord_tables.twist[twist][m] as usize,
+                coord_tables.flip[flip][m] as usize,
+                coord_tables.slice_edges[slice_position * SLICE_ORDERS][m] as usize / SLICE_ORDERS,
+                depth - 1,
+            );
+            if found {
+                return true;
+            }
+            self.path.pop();
+        }
+
+        false
+    }
+
+    /// Runs phase 2 on the state path leads to, with the moves left under max_depth.
+    fn start_phase_2(&mut self) -> bool {
+        let all_moves = moves();
+        let mut cube = self.start;
+        self.path.iter().for_each(|m| cube.apply(&all_moves[*m]));
+
+        let mut pieces = Pieces::solved();
+        pieces.edge_permutation = cube.edge_permutation();
+        let corners = cube.corner_permutation as usize;
+        let edges = layer_edges(&pieces);
+        let slice = slice_permutation(&pieces);
+
+        let phase_1_length = self.path.len();
+        (0..=self.max_depth - phase_1_length).any(|depth| self.phase_2(corners, edges, slice, depth))
+    }
+
+    /// Extends path by depth phase 2 moves that solve a state in G1 with the specified coordinates.
+    fn phase_2(&mut self, corners: usize, edges: usize, slice: usize, depth: usize) -> bool {
+        let estimate = self.tables.corner_slice[corners * SLICE_ORDERS + slice]
+            .max(self.tables.layer_edge_slice[edges * SLICE_ORDERS + slice]);
+        if estimate as usize > depth {
+            return false;
+        }
+        if depth == 0 {
+            return true;
+        }
+
+        let coord_tables = move_tables();
+        for m in (0..NUM_MOVES).filter(|m| is_phase_2_move(*m)) {
+            if is_redundant(self.path.last().copied(), m) {
+                continue;
+            }
+
+            self.path.push(m);
+            let found = self.phase_2(
+                coord_tables.corner_permutation[corners][m] as usize,
+                self.tables.layer_edges[edges][m] as usize,
+                self.tables.slice_permutation[slice][m] as usize,
+                depth - 1,
+            );
+            if found {
+                return true;
+            }
+            self.path.pop();
+        }
+
+        false
+    }
+}
+
+impl CoordCube {
+    /// Solves the cube with Kociemba's two-phase algorithm, returning the first solution it finds
+    /// with no more than max_depth moves, counting double turns as one. Solutions are usually
+    /// within a few moves of the shortest. With a max_depth of 24 or more a solution is usually
+    /// found within milliseconds, but the search slows down sharply as max_depth approaches the
+    /// shortest solution. The lookup tables are built on the first call. Returns None if there is
+    /// no solution that short.
+    pub fn solve_two_phase(&self, max_depth: usize) -> Option<Vec<Rotation>> {
+        let tables = TABLES.get_or_init(build_tables);
+        let mut search = Search { tables, start: *self, max_depth, path: Vec::new() };
+
+        let twist = self.twist as usize;
+        let flip = self.flip as usize;
+        let slice_position = self.slice_edges as usize / SLICE_ORDERS;
+        let found = (0..=max_depth).any(|depth| search.phase_1(twist, flip, slice_position, depth));
+
+        let all_moves = moves();
+        found.then(|| search.path.iter().map(|m| all_moves[*m].clone()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::rubiks_cube::{verify, RubiksCube};
+
+    fn assert_solves(cube: &RubiksCube) {
+        let solution = CoordCube::from_cube(cube).solve_two_phase(24).unwrap();
+        assert!(solution.len() <= 24);
+        assert!(verify(cube, &solution));
+    }
+
+    #[test]
+    fn solves_random_scrambles() {
+        for seed in 0..50 {
+            let mut cube = RubiksCube::solved();
+            cube.scramble_seeded(seed, 40);
+            assert_solves(&cube);
+        }
+    }
+
+    #[test]
+    fn solves_the_superflip() {
+        assert_solves(&RubiksCube::superflip());
+    }
+}
@@ -0,0 +1,429 @@
+//! A layer by layer solver following the beginner's method.
+//!
+//! The cube is solved with white as the first layer: the white cross, the white corners, the
+//! middle edges, the yellow cross, then the yellow edges, corner positions and corner orientations.
+//! Each step inspects where the pieces it needs are and applies fixed algorithms until they are
+//! home, so it always finishes quickly but its solutions are far from optimal.
+
+use super::color::{Color, WHITE, YELLOW, ALL_COLORS};
+use super::algorithms::{sexy_move, sune};
+use super::rotation::{parse_sequence_with, Direction, Notation, Rotation};
+use super::rubiks_cube::RubiksCube;
+
+// The number of times any single step is retried before giving up. Every step normally finishes
+// in a handful of iterations; this only guards against looping forever on an invalid cube.
+const MAX_ATTEMPTS: usize = 12;
+
+/// Names the faces as seen when holding the cube with yellow on top and front facing you, so that
+/// algorithms can be written in the usual U/D/F/B/R/L notation. The first layer ends up as D.
+struct Frame {
+    front: &'static Color,
+    back: &'static Color,
+    right: &'static Color,
+    left: &'static Color,
+}
+
+impl Frame {
+    /// Returns the frame with the specified side color as the front.
+    fn new(front: &'static Color) -> Self {
+        // Yellow's neighbors go clockwise when looking down on yellow, which is right to left when
+        // looking at any one of them.
+        let sides = YELLOW.adjacent();
+        let i = sides.iter().position(|side| *side == front).expect("front must be a side");
+        Frame {
+            front,
+            left: sides[(i + 1) % sides.len()],
+            back: sides[(i + 2) % sides.len()],
+            right: sides[(i + 3) % sides.len()],
+        }
+    }
+
+    /// Returns the frame whose front and right faces are a and b in some order.
+    fn with_sides(a: &'static Color, b: &'static Color) -> Self {
+        let frame = Frame::new(a);
+        if frame.right == b { frame } else { Frame::new(b) }
+    }
+
+    /// Moves an algorithm written for the orientation of Notation::Standard, such as the ones in
+    /// algorithms, into this frame.
+    fn translate(&self, algorithm: Vec<Rotation>) -> Vec<Rotation> {
+        let mut faces = ALL_COLORS;
+        for (letter, face) in [("U", &YELLOW), ("D", &WHITE), ("F", self.front), ("B", self.back),
+                               ("R", self.right), ("L", self.left)] {
+            let standard = Notation::Standard.face(letter).expect("U/D/F/B/R/L are standard faces");
+            faces[standard.idx] = face;
+        }
+
+        algorithm.into_iter()
+            .map(|rotation| Rotation { face: faces[rotation.face.idx], direction: rotation.direction })
+            .collect()
+    }
+
+    /// Translates an algorithm written in U/D/F/B/R/L notation into rotations.
+    fn alg(&self, notation: &str) -> Vec<Rotation> {
+        let algorithm = parse_sequence_with(notation, Notation::Standard)
+            .expect("Built in algorithms are valid notation");
+        self.translate(algorithm)
+    }
+}
+
+/// Returns the static color equal to color.
+fn to_static(color: &Color) -> &'static Color {
+    ALL_COLORS[color.idx]
+}
+
+fn single(face: &'static Color, direction: Direction) -> Vec<Rotation> {
+    vec![Rotation { face, direction }]
+}
+
+/// A cube being solved along with the rotations applied to it so far, each with the step that
+/// applied it.
+struct Layered<'a> {
+    cube: RubiksCube<'a>,
+    moves: Vec<(Rotation, &'static str)>,
+    /// What the step currently running accomplishes.
+    step: &'static str,
+}
+
+impl <'a> Layered<'a> {
+    fn run(&mut self, rotations: Vec<Rotation>) {
+        for rotation in rotations {
+            self.cube.apply(&rotation);
+            self.moves.push((rotation, self.step));
+        }
+    }
+
+    /// Returns the face that the sticker of the specified color on the specified piece is on.
+    fn face_of(&self, piece: &[&Color], color: &Color) -> &'a Color {
+        self.cube.find_block(piece)
+            .and_then(|block| block.face_of(color))
+            .expect("Every piece has a sticker of each of its colors")
+    }
+
+    /// Returns the faces the specified piece is currently touching.
+    fn faces(&self, piece: &[&'static Color]) -> Vec<&'a Color> {
+        piece.iter().map(|color| self.face_of(piece, color)).collect()
+    }
+
+    fn touches(&self, piece: &[&'static Color], face: &Color) -> bool {
+        self.faces(piece).contains(&face)
+    }
+
+    fn is_home(&self, piece: &[&'static Color]) -> bool {
+        piece.iter().all(|color| self.face_of(piece, color) == *color)
+    }
+
+    /// Turns the top layer until the predicate is satisfied.
+    fn turn_top_until(&mut self, predicate: impl Fn(&Self) -> bool) {
+        for _ in 0..4 {
+            if predicate(self) {
+                return;
+            }
+            self.run(single(&YELLOW, Direction::Clockwise));
+        }
+    }
+
+    /// Places the white edge whose other color is side.
+    fn solve_cross_edge(&mut self, side: &'static Color) {
+        let piece = [&WHITE, side];
+        let frame = Frame::new(side);
+
+        for _ in 0..MAX_ATTEMPTS {
+            if self.is_home(&piece) {
+                return;
+            }
+
+            let faces = self.faces(&piece);
+            if faces.contains(&&WHITE) {
+                // In the bottom layer but wrong, so lift it to the top layer.
+                let other = faces.into_iter().find(|face| *face != &WHITE).unwrap();
+                self.run(single(to_static(other), Direction::Double));
+            } else if !faces.contains(&&YELLOW) {
+                // In the middle layer. Move it to the top and restore whatever bottom edge moved.
+                let face = to_static(self.face_of(&piece, &WHITE));
+                let mut trial = self.cube.clone();
+                trial.apply(&Rotation { face, direction: Direction::Clockwise });
+                let (out, back) = match trial.find_block(&piece).unwrap().get_face(&YELLOW) {
+                    Some(_) => (Direction::Clockwise, Direction::CounterClockwise),
+                    None => (Direction::CounterClockwise, Direction::Clockwise),
+                };
+                self.run(vec![
+                    Rotation { face, direction: out },
+                    Rotation { face: &YELLOW, direction: Direction::Clockwise },
+                    Rotation { face, direction: back },
+                ]);
+            } else if !faces.contains(&side) {
+                self.turn_top_until(|layered| layered.touches(&piece, side));
+            } else if self.face_of(&piece, side) == side {
+                self.run(frame.alg("F2"));
+            } else {
+                self.run(frame.alg("U' R' F R"));
+            }
+        }
+    }
+
+    /// Places the white corner whose other colors are a and b.
+    fn solve_first_layer_corner(&mut self, a: &'static Color, b: &'static Color) {
+        let piece = [&WHITE, a, b];
+        let frame = Frame::with_sides(a, b);
+
+        for _ in 0..MAX_ATTEMPTS {
+            if self.is_home(&piece) {
+                return;
+            }
+
+            let faces = self.faces(&piece);
+            if faces.contains(&&WHITE) {
+                // In the wrong bottom slot or twisted, so lift it to the top layer.
+                let sides: Vec<&'static Color> = faces.into_iter()
+                    .filter(|face| *face != &WHITE)
+                    .map(to_static)
+                    .collect();
+                self.run(Frame::with_sides(sides[0], sides[1]).translate(sexy_move()));
+            } else {
+                self.turn_top_until(|layered| layered.touches(&piece, a) && layered.touches(&piece, b));
+                for _ in 0..6 {
+                    if self.is_home(&piece) {
+                        break;
+                    }
+                    self.run(frame.translate(sexy_move()));
+                }
+            }
+        }
+    }
+
+    /// Places the middle layer edge with colors a and b.
+    fn solve_middle_edge(&mut self, a: &'static Color, b: &'static Color) {
+        let piece = [a, b];
+
+        for _ in 0..MAX_ATTEMPTS {
+            if self.is_home(&piece) {
+                return;
+            }
+
+            let faces = self.faces(&piece);
+            if !faces.contains(&&YELLOW) {
+                // In the wrong middle slot or flipped, so swap it out for whatever is on top.
+                let sides: Vec<&'static Color> = faces.into_iter().map(to_static).collect();
+                self.run(Frame::with_sides(sides[0], sides[1]).alg("U R U' R' U' F' U F"));
+                continue;
+            }
+
+            // Line the side sticker up with its center then insert it away from the top sticker.
+            let (side, top) = if self.face_of(&piece, a) == &YELLOW { (b, a) } else { (a, b) };
+            self.turn_top_until(|layered| layered.face_of(&piece, side) == side);
+            let frame = Frame::new(side);
+            if frame.right == top {
+                self.run(frame.alg("U R U' R' U' F' U F"));
+            } else {
+                self.run(frame.alg("U' L' U L U F U' F'"));
+            }
+        }
+    }
+
+    fn top_edge_oriented(&self, side: &'static Color) -> bool {
+        let position = [&YELLOW, side];
+        let block = self.cube.find_edge(&position).unwrap();
+        block.get_face(&YELLOW) == Some(&YELLOW)
+    }
+
+    /// Orients the top layer edges so they form a yellow cross.
+    fn solve_top_cross(&mut self) {
+        for _ in 0..MAX_ATTEMPTS {
+            let sides = YELLOW.adjacent();
+            if sides.iter().all(|side| self.top_edge_oriented(side)) {
+                return;
+            }
+
+            // Hold a line horizontally, or an L shape at the back and left. With neither
+            // (a single dot) any front will do.
+            let front = sides.iter()
+                .find(|side| {
+                    let frame = Frame::new(side);
+                    self.top_edge_oriented(frame.left) && self.top_edge_oriented(frame.right)
+                })
+                .or_else(|| sides.iter().find(|side| {
+                    let frame = Frame::new(side);
+                    self.top_edge_oriented(frame.left) && self.top_edge_oriented(frame.back)
+                }))
+                .unwrap_or(&sides[0]);
+            self.run(Frame::new(front).alg("F R U R' U' F'"));
+        }
+    }
+
+    fn count_home_top_edges(&self) -> usize {
+        YELLOW.adjacent().iter()
+            .filter(|side| self.is_home(&[&YELLOW, side]))
+            .count()
+    }
+
+    /// Moves the top layer edges to their home positions, keeping them oriented.
+    fn solve_top_edges(&mut self) {
+        for _ in 0..MAX_ATTEMPTS {
+            let mut best = (0, self.count_home_top_edges());
+            for turns in 1..4 {
+                self.cube.apply(&Rotation { face: &YELLOW, direction: Direction::Clockwise });
+                let count = self.count_home_top_edges();
+                if count > best.1 {
+                    best = (turns, count);
+                }
+            }
+            self.cube.apply(&Rotation { face: &YELLOW, direction: Direction::Clockwise });
+            for _ in 0..best.0 {
+                self.run(single(&YELLOW, Direction::Clockwise));
+            }
+            if best.1 == 4 {
+                return;
+            }
+
+            // Hold two adjacent solved edges at the back and right, otherwise any front will do.
+            let sides = YELLOW.adjacent();
+            let front = sides.iter()
+                .find(|side| {
+                    let frame = Frame::new(side);
+                    self.is_home(&[&YELLOW, frame.back]) && self.is_home(&[&YELLOW, frame.right])
+                })
+                .unwrap_or(&sides[0]);
+            let frame = Frame::new(front);
+            self.run(frame.translate(sune()));
+            self.run(frame.alg("U"));
+        }
+    }
+
+    /// Returns whether the top corner between side and the side to its right holds the right piece,
+    /// in any orientation.
+    fn top_corner_placed(&self, side: &'static Color) -> bool {
+        let right = Frame::new(side).right;
+        let position = [&YELLOW, side, right];
+        let block = self.cube.find_corner(&position).unwrap();
+        position.iter().all(|color| block.face_of(color).is_some())
+    }
+
+    /// Moves the top layer corners to their home positions, ignoring their orientation.
+    fn solve_top_corner_positions(&mut self) {
+        for _ in 0..MAX_ATTEMPTS {
+            let sides = YELLOW.adjacent();
+            if sides.iter().all(|side| self.top_corner_placed(side)) {
+                return;
+            }
+
+            // Hold a correctly placed corner at the front right, otherwise any front will do.
+            let front = sides.iter()
+                .find(|side| self.top_corner_placed(side))
+                .unwrap_or(&sides[0]);
+            self.run(Frame::new(front).alg("U R U' L' U R' U' L"));
+        }
+    }
+
+    fn top_corner_oriented(&self, side: &'static Color) -> bool {
+        let position = [&YELLOW, side, Frame::new(side).right];
+        let block = self.cube.find_corner(&position).unwrap();
+        block.get_face(&YELLOW) == Some(&YELLOW)
+    }
+
+    /// Twists the top layer corners so yellow faces up, finishing the cube.
+    fn solve_top_corner_orientations(&mut self) {
+        let sides = YELLOW.adjacent();
+        let frame = Frame::new(sides[0]);
+
+        // Twist each corner in turn at the front right. The bottom layers are only restored once
+        // every corner is done.
+        for _ in 0..sides.len() {
+            if sides.iter().all(|side| self.top_corner_oriented(side)) {
+                break;
+            }
+            for _ in 0..MAX_ATTEMPTS {
+                if self.top_corner_oriented(frame.front) {
+                    break;
+                }
+                self.run(frame.alg("R' D' R D"));
+            }
+            self.run(frame.alg("U"));
+        }
+
+        self.turn_top_until(|layered| layered.is_home(&[&YELLOW, frame.front]));
+    }
+}
+
+impl <'a> RubiksCube<'a> {
+    /// Solves the cube with the beginner's layer by layer method. The result is deterministic and
+    /// is found quickly regardless of how scrambled the cube is, but is usually over 100 moves.
+    pub fn solve_layered(&self) -> Vec<Rotation> {
+        self.solve_layered_explained().into_iter()
+            .map(|(rotation, _)| rotation)
+            .collect()
+    }
+
+    /// Like solve_layered, but pairs each rotation with a description of the step of the method
+    /// it belongs to, such as "solve white cross".
+    pub fn solve_layered_explained(&self) -> Vec<(Rotation, &'static str)> {
+        let mut layered = Layered { cube: self.clone(), moves: Vec::new(), step: "" };
+        let sides = YELLOW.adjacent();
+
+        layered.step = "solve white cross";
+        for side in sides {
+            layered.solve_cross_edge(side);
+        }
+        layered.step = "insert white corners";
+        for i in 0..sides.len() {
+            layered.solve_first_layer_corner(sides[i], sides[(i + 1) % sides.len()]);
+        }
+        layered.step = "insert middle layer edges";
+        for i in 0..sides.len() {
+            layered.solve_middle_edge(sides[i], sides[(i + 1) % sides.len()]);
+        }
+        layered.step = "orient yellow edges";
+        layered.solve_top_cross();
+        layered.step = "permute yellow edges";
+        layered.solve_top_edges();
+        layered.step = "permute yellow corners";
+        layered.solve_top_corner_positions();
+        layered.step = "orient yellow corners";
+        layered.solve_top_corner_orientations();
+
+        layered.moves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::model::rubiks_cube::verify;
+
+    #[test]
+    fn solve_layered_solves_random_scrambles() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..50 {
+            let mut cube = RubiksCube::solved();
+            cube.scramble(&mut rng, 30);
+            assert!(verify(&cube, &cube.solve_layered()));
+        }
+    }
+
+    #[test]
+    fn solve_layered_explained_tags_every_move_with_its_step() {
+        let steps = [
+            "solve white cross",
+            "insert white corners",
+            "insert middle layer edges",
+            "orient yellow edges",
+            "permute yellow edges",
+            "permute yellow corners",
+            "orient yellow corners",
+        ];
+        let mut cube = RubiksCube::solved();
+        cube.scramble_seeded(602, 30);
+
+        let explained = cube.solve_layered_explained();
+        let order: Vec<usize> = explained.iter()
+            .map(|(_, step)| steps.iter().position(|known| known == step).unwrap())
+            .collect();
+        assert!(order.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        let solution: Vec<Rotation> = explained.into_iter().map(|(rotation, _)| rotation).collect();
+        assert!(verify(&cube, &solution));
+    }
+}
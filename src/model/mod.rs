@@ -2,3 +2,15 @@ pub mod color;
 pub mod block;
 pub mod rotation;
 pub mod rubiks_cube;
+pub mod algorithms;
+pub mod pruning;
+pub mod layered;
+pub mod cross;
+pub mod thistlethwaite;
+pub mod pocket_cube;
+pub mod session;
+pub mod json;
+pub mod move_table;
+pub mod coord_cube;
+pub mod kociemba;
+pub mod optimal;
@@ -0,0 +1,65 @@
+//! Move tables for coordinate models, which number the values of some property of the cube and
+//! need to know the value each move takes every other value to.
+
+/// Returns the table whose entry [i][m] is the value reached by applying moves[m] to value i, for
+/// every value below size. decode makes any state with the specified value, encode reads the value
+/// back from a state and apply returns a state after a move. The value reached must not depend on
+/// which state decode picks, which holds as long as the property is one the moves act on alone.
+///
+/// Panics if encode returns a value that doesn't fit in a u16.
+pub fn build_move_table<C, M>(
+    size: usize,
+    decode: impl Fn(usize) -> C,
+    encode: impl Fn(&C) -> usize,
+    moves: &[M],
+    apply: impl Fn(&C, &M) -> C,
+) -> Vec<Vec<u16>> {
+    (0..size)
+        .map(|i| {
+            let state = decode(i);
+            moves.iter()
+                .map(|m| encode(&apply(&state, m)).try_into().expect("Coordinate values fit in a u16"))
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::coord_cube::{moves, move_tables, CoordCube, CORNER_PERMUTATION_SIZE, FLIP_SIZE, TWIST_SIZE};
+
+    // Checks every step-th value of a coordinate: the table's entry for each move must be the
+    // coordinate of a cube with that value after turning it.
+    fn assert_matches_turns(
+        table: &[Vec<u16>],
+        size: usize,
+        step: usize,
+        with: impl Fn(u16) -> CoordCube,
+        get: impl Fn(&CoordCube) -> u16,
+    ) {
+        assert_eq!(table.len(), size);
+        for value in (0..size).step_by(step) {
+            let cube = with(value as u16).to_cube();
+            for (m, rotation) in moves().iter().enumerate() {
+                let mut turned = cube.clone();
+                turned.turn(rotation);
+                assert_eq!(table[value][m], get(&CoordCube::from_cube(&turned)));
+            }
+        }
+    }
+
+    #[test]
+    fn sampled_entries_match_turning_the_cube() {
+        let tables = move_tables();
+        let solved = CoordCube::solved();
+        assert_matches_turns(&tables.twist, TWIST_SIZE, 97, |twist| CoordCube { twist, ..solved }, |cube| cube.twist);
+        assert_matches_turns(&tables.flip, FLIP_SIZE, 89, |flip| CoordCube { flip, ..solved }, |cube| cube.flip);
+        assert_matches_turns(
+            &tables.corner_permutation,
+            CORNER_PERMUTATION_SIZE,
+            1009,
+            |corner_permutation| CoordCube { corner_permutation, ..solved },
+            |cube| cube.corner_permutation,
+        );
+    }
+}
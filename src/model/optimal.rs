@@ -0,0 +1,127 @@
+//! An optimal solver: an iterative deepening A* search over CoordCube guided by pruning tables,
+//! each giving the distance to solved of one or two of the coordinates alone. Every state is
+//! within 20 moves of solved, so the search never goes deeper than that.
+
+use std::sync::OnceLock;
+
+use super::coord_cube::{
+    move_tables, moves, CoordCube, CORNER_PERMUTATION_SIZE, EDGE_GROUP_SIZE, FLIP_SIZE, NUM_MOVES,
+    TWIST_SIZE,
+};
+use super::kociemba::{is_redundant, next, pruning_table};
+use super::rotation::Rotation;
+
+// The largest number of moves, counting double turns as one, that any state needs.
+const GODS_NUMBER: usize = 20;
+
+static TABLES: OnceLock<Tables> = OnceLock::new();
+
+/// The distance to solved of each value of the coordinates named, indexed like
+/// kociemba::pruning_table.
+struct Tables {
+    orientations: Vec<u8>,
+    corners: Vec<u8>,
+    slice_edges: Vec<u8>,
+    white_edges: Vec<u8>,
+    yellow_edges: Vec<u8>,
+}
+
+impl Tables {
+    /// Returns the largest of the lower bounds on the number of moves needed to solve cube.
+    fn estimate(&self, cube: &CoordCube) -> usize {
+        [
+            self.orientations[cube.twist as usize * FLIP_SIZE + cube.flip as usize],
+            self.corners[cube.corner_permutation as usize],
+            self.slice_edges[cube.slice_edges as usize],
+            self.white_edges[cube.white_edges as usize],
+            self.yellow_edges[cube.yellow_edges as usize],
+        ].into_iter().max().unwrap_or(0) as usize
+    }
+}
+
+fn build_tables() -> Tables {
+    let coord_tables = move_tables();
+    let all_moves: Vec<usize> = (0..NUM_MOVES).collect();
+    let solved = CoordCube::solved();
+    let none = |_, _| 0;
+    let single = |size: usize, table: &[Vec<u16>], goal: u16| {
+        pruning_table((size, 1), (goal as usize, 0), &all_moves, next(table), none)
+    };
+
+    Tables {
+        orientations: pruning_table(
+            (TWIST_SIZE, FLIP_SIZE),
+            (solved.twist as usize, solved.flip as usize),
+            &all_moves,
+            next(&coord_tables.twist),
+            next(&coord_tables.flip),
+        ),
+        corners: single(CORNER_PERMUTATION_SIZE, &coord_tables.corner_permutation, solved.corner_permutation),
+        slice_edges: single(EDGE_GROUP_SIZE, &coord_tables.slice_edges, solved.slice_edges),
+        white_edges: single(EDGE_GROUP_SIZE, &coord_tables.white_edges, solved.white_edges),
+        yellow_edges: single(EDGE_GROUP_SIZE, &coord_tables.yellow_edges, solved.yellow_edges),
+    }
+}
+
+/// Extends path by depth moves that solve cube, returning whether it found them.
+fn search(tables: &Tables, all_moves: &[Rotation], cube: CoordCube, depth: usize, path: &mut Vec<usize>) -> bool {
+    if tables.estimate(&cube) > depth {
+        return false;
+    }
+    if depth == 0 {
+        return cube.is_solved();
+    }
+
+    for m in 0..NUM_MOVES {
+        if is_redundant(path.last().copied(), m) {
+            continue;
+        }
+
+        let mut next = cube;
+        next.apply(&all_moves[m]);
+        path.push(m);
+        if search(tables, all_moves, next, depth - 1, path) {
+            return true;
+        }
+        path.pop();
+    }
+
+    false
+}
+
+impl CoordCube {
+    /// Returns a shortest solution, counting double turns as one move. The pruning tables are only
+    /// good enough for this to be quick when the cube is within about 12 moves of solved; beyond
+    /// that each extra move multiplies the time taken by roughly ten, so the hardest states are
+    /// out of reach. The lookup tables are built on the first call. Returns None only if self
+    /// can't be solved.
+    pub fn solve_optimal(&self) -> Option<Vec<Rotation>> {
+        let tables = TABLES.get_or_init(build_tables);
+        let all_moves = moves();
+        let mut path = Vec::new();
+
+        let found = (0..=GODS_NUMBER).any(|depth| search(tables, &all_moves, *self, depth, &mut path));
+        found.then(|| path.iter().map(|m| all_moves[*m].clone()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::rubiks_cube::{verify, RubiksCube};
+
+    #[test]
+    fn solutions_are_no_longer_than_shallow_scrambles() {
+        for seed in 0..5 {
+            let mut cube = RubiksCube::solved();
+            let scramble = cube.scramble_seeded(seed, 6);
+
+            let solution = CoordCube::from_cube(&cube).solve_optimal().unwrap();
+            assert!(solution.len() <= scramble.len());
+            assert!(verify(&cube, &solution));
+        }
+
+        let cube = RubiksCube::from_scramble("w r2 b'").unwrap();
+        assert_eq!(CoordCube::from_cube(&cube).solve_optimal().unwrap().len(), 3);
+    }
+}
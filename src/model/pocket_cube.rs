@@ -0,0 +1,279 @@
+//! The 2x2x2 pocket cube, which is a RubiksCube with only its 8 corners. Without centers there is
+//! nothing to fix the cube's orientation, so it is solved whenever every face shows one color.
+
+use std::sync::OnceLock;
+use rand::Rng;
+
+use super::color::{Color, NUM_COLORS, WHITE, RED, BLUE};
+use super::coord_cube::permutation_index;
+use super::rotation::{Direction, Rotation};
+use super::rubiks_cube::{corner_positions, get_color_rotations, symmetries};
+
+const NUM_CORNERS: usize = 8;
+
+// The orderings of the 7 corners that move times the twists of 6 of them, the last one's twist
+// following from the others.
+const NUM_STATES: usize = 5040 * 729;
+
+// Turning a face is the same as turning the opposite face and then the whole cube, so searching
+// with turns of one face from each opposite pair reaches every state.
+const SEARCH_FACES: [&Color; 3] = [&WHITE, &RED, &BLUE];
+
+/// The corner positions as face indices, kept around so indexing a state doesn't allocate.
+struct Positions {
+    faces: [[u8; 3]; NUM_CORNERS],
+    // The position touching each set of faces, as a mask of their indices.
+    by_mask: [usize; 1 << NUM_COLORS],
+    // The one position no turn of SEARCH_FACES moves.
+    fixed: usize,
+}
+
+impl Positions {
+    fn new() -> Self {
+        let mut faces = [[0; 3]; NUM_CORNERS];
+        let mut by_mask = [0; 1 << NUM_COLORS];
+        for (i, (corner, position)) in faces.iter_mut().zip(corner_positions()).enumerate() {
+            *corner = position.map(|color| color.idx as u8);
+            by_mask[mask(corner)] = i;
+        }
+
+        let search_mask = SEARCH_FACES.iter().fold(0, |mask, face| mask | 1 << face.idx);
+        let fixed = faces.iter()
+            .position(|corner| mask(corner) & search_mask == 0)
+            .expect("Some corner touches none of the search faces");
+        Positions { faces, by_mask, fixed }
+    }
+
+    fn of(&self, faces: &[u8; 3]) -> usize {
+        self.by_mask[mask(faces)]
+    }
+}
+
+fn mask(faces: &[u8; 3]) -> usize {
+    1 << faces[0] | 1 << faces[1] | 1 << faces[2]
+}
+
+struct Tables {
+    positions: Positions,
+    // The number of moves each state, by index, needs to be solved.
+    distances: Vec<u8>,
+}
+
+static TABLES: OnceLock<Tables> = OnceLock::new();
+
+/// A turn as face indices: the face turned and the face each sticker on it moves onto.
+struct Move {
+    face: u8,
+    targets: [u8; NUM_COLORS],
+}
+
+impl Move {
+    fn new(rotation: &Rotation) -> Self {
+        let rotations = get_color_rotations(rotation);
+        let mut targets = [0; NUM_COLORS];
+        for (target, rotated) in targets.iter_mut().zip(rotations) {
+            *target = rotated.map_or(0, |color| color.idx as u8);
+        }
+        Move { face: rotation.face.idx as u8, targets }
+    }
+}
+
+fn search_moves() -> Vec<Rotation> {
+    SEARCH_FACES.iter()
+        .flat_map(|face| [Direction::Clockwise, Direction::CounterClockwise, Direction::Double]
+            .map(|direction| Rotation { face, direction }))
+        .collect()
+}
+
+/// Builds the distance of every state from solved with a breadth first search out from it.
+fn build_tables() -> Tables {
+    let positions = Positions::new();
+    let moves: Vec<Move> = search_moves().iter().map(Move::new).collect();
+    let mut distances = vec![u8::MAX; NUM_STATES];
+
+    let solved = PocketCube::solved();
+    distances[solved.index(&positions)] = 0;
+    let mut frontier = vec![solved];
+    let mut depth = 0;
+
+    while !frontier.is_empty() {
+        depth += 1;
+        let mut next_frontier = Vec::new();
+        for cube in frontier {
+            for m in &moves {
+                let mut next = cube;
+                next.apply(m);
+
+                let idx = next.index(&positions);
+                if distances[idx] == u8::MAX {
+                    distances[idx] = depth;
+                    next_frontier.push(next);
+                }
+            }
+        }
+        frontier = next_frontier;
+    }
+
+    Tables { positions, distances }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct PocketCube {
+    // For each corner in the order of corner_positions, the idx of the face each of its stickers is
+    // on, where its stickers are the colors of that position. Indices rather than Colors keep
+    // states small enough for solve to hold millions of them.
+    faces: [[u8; 3]; NUM_CORNERS],
+}
+
+impl PocketCube {
+    pub fn solved() -> Self {
+        let mut faces = [[0; 3]; NUM_CORNERS];
+        for (corner, position) in faces.iter_mut().zip(corner_positions()) {
+            *corner = position.map(|color| color.idx as u8);
+        }
+        PocketCube { faces }
+    }
+
+    /// Returns whether every face shows a single color, which need not be the face's own color.
+    pub fn is_solved(&self) -> bool {
+        let mut shown: [Option<&Color>; NUM_COLORS] = [None; NUM_COLORS];
+
+        for (faces, colors) in self.faces.iter().zip(corner_positions()) {
+            for (face, color) in faces.iter().zip(colors) {
+                match shown[*face as usize] {
+                    None => shown[*face as usize] = Some(color),
+                    Some(other) if other != color => return false,
+                    Some(_) => (),
+                }
+            }
+        }
+
+        true
+    }
+
+    pub fn turn(&mut self, rotation: &Rotation) {
+        self.apply(&Move::new(rotation));
+    }
+
+    fn apply(&mut self, m: &Move) {
+        for faces in self.faces.iter_mut() {
+            if faces.contains(&m.face) {
+                *faces = faces.map(|face| m.targets[face as usize]);
+            }
+        }
+    }
+
+    pub fn scramble(&mut self, rng: &mut impl Rng, n: usize) -> Vec<Rotation> {
+        let mut result = Vec::new();
+        for _ in 0..n {
+            let rotation = Rotation::random(rng);
+            self.turn(&rotation);
+            result.push(rotation);
+        }
+
+        result
+    }
+
+    /// Returns the same state seen with the whole cube turned so the corner at the fixed position
+    /// is in place, which index needs.
+    fn normalized(&self, positions: &Positions) -> Self {
+        let piece = self.faces.iter()
+            .position(|faces| positions.of(faces) == positions.fixed)
+            .expect("Some corner is at every position");
+        let symmetry = symmetries().iter()
+            .find(|symmetry| positions.faces[piece].iter().zip(self.faces[piece])
+                .all(|(color, face)| symmetry[*color as usize].idx as u8 == face))
+            .expect("Some whole-cube rotation puts any corner in place");
+
+        let mut result = *self;
+        for (colors, faces) in positions.faces.iter().zip(self.faces) {
+            let colors = colors.map(|color| symmetry[color as usize].idx as u8);
+            let piece = positions.of(&colors);
+            for (color, face) in colors.iter().zip(faces) {
+                let sticker = positions.faces[piece].iter().position(|other| other == color).unwrap();
+                result.faces[piece][sticker] = face;
+            }
+        }
+        result
+    }
+
+    /// Returns the state's index in the distance table. The corner at the fixed position must be in
+    /// place.
+    fn index(&self, positions: &Positions) -> usize {
+        let mut pieces = [0; NUM_CORNERS];
+        let mut twists = [0; NUM_CORNERS];
+        for (piece, faces) in self.faces.iter().enumerate() {
+            let position = positions.of(faces);
+            pieces[position] = piece as u8;
+            let home = &positions.faces[position];
+            twists[position] = if home[0] == faces[0] { 0 } else if home[1] == faces[0] { 1 } else { 2 };
+        }
+
+        let mut order = [0; NUM_CORNERS - 1];
+        let mut twist = 0;
+        let mut i = 0;
+        for position in 0..NUM_CORNERS {
+            if position == positions.fixed {
+                continue;
+            }
+            order[i] = pieces[position];
+            if i < NUM_CORNERS - 2 {
+                twist = twist * 3 + twists[position];
+            }
+            i += 1;
+        }
+
+        permutation_index(&order) * 729 + twist
+    }
+
+    /// Returns a shortest solution counting double turns as one move, found by walking down a table
+    /// of how far every state is from solved. The table is built on the first call; there are only
+    /// about 3.7 million states once the cube's orientation is ignored, and none is more than 11
+    /// moves from solved.
+    pub fn solve(&self) -> Vec<Rotation> {
+        let Tables { positions, distances } = TABLES.get_or_init(build_tables);
+        let moves = search_moves();
+
+        let mut cube = self.normalized(positions);
+        let mut result = Vec::new();
+        while distances[cube.index(positions)] > 0 {
+            let distance = distances[cube.index(positions)];
+            let (next, rotation) = moves.iter()
+                .map(|rotation| {
+                    let mut next = cube;
+                    next.turn(rotation);
+                    (next, rotation)
+                })
+                .find(|(next, _)| distances[next.index(positions)] < distance)
+                .expect("Every unsolved state has a neighbor closer to solved");
+            cube = next;
+            result.push(rotation.clone());
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use super::*;
+
+    #[test]
+    fn solves_scrambles_in_at_most_11_moves() {
+        assert!(PocketCube::solved().solve().is_empty());
+
+        let mut rng = StdRng::seed_from_u64(559);
+        for _ in 0..20 {
+            let mut cube = PocketCube::solved();
+            cube.scramble(&mut rng, 30);
+
+            let solution = cube.solve();
+            assert!(solution.len() <= 11);
+            solution.iter().for_each(|rotation| cube.turn(rotation));
+            assert!(cube.is_solved());
+        }
+    }
+}
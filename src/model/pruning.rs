@@ -0,0 +1,255 @@
+//! Pattern databases giving lower bounds on the number of quarter turns needed to solve a cube.
+//!
+//! Each table is built once by a breadth first search from the solved cube over a coordinate that
+//! captures part of the cube's state. Since solving the whole cube also solves that part, the
+//! distance stored for a cube's coordinate never exceeds the cube's true distance.
+
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+
+use rand::Rng;
+
+use super::rotation::Rotation;
+use super::rubiks_cube::RubiksCube;
+
+// The orientation of the last corner is determined by the other seven.
+const CORNER_ORIENTATION_STATES: usize = 2187; // 3^7
+
+static CORNER_TABLE: OnceLock<Vec<u8>> = OnceLock::new();
+static PIECE_TABLES: OnceLock<PieceTables> = OnceLock::new();
+
+// A quarter turn moves four corners and four edges.
+const PIECES_PER_TURN: usize = 4;
+
+// Every lookup that gives a lower bound on the quarter turns needed to solve a cube.
+const HEURISTICS: [fn(&RubiksCube) -> u8; 2] = [corner_heuristic, |cube| cube.lower_bound()];
+
+/// Encodes the orientations of the first seven corners as a base 3 number.
+fn corner_orientation_index(cube: &RubiksCube) -> usize {
+    cube.corner_orientations()[..7].iter()
+        .rev()
+        .fold(0, |acc, orientation| acc * 3 + *orientation as usize)
+}
+
+fn build_corner_table() -> Vec<u8> {
+    let mut table = vec![u8::MAX; CORNER_ORIENTATION_STATES];
+    let all_rotations = Rotation::all();
+
+    let solved = RubiksCube::solved();
+    table[corner_orientation_index(&solved)] = 0;
+    let mut queue = VecDeque::from([solved]);
+
+    while let Some(cube) = queue.pop_front() {
+        let depth = table[corner_orientation_index(&cube)];
+
+        for rotation in all_rotations.iter() {
+            let mut next = cube.clone();
+            next.apply(rotation);
+
+            let idx = corner_orientation_index(&next);
+            if table[idx] == u8::MAX {
+                table[idx] = depth + 1;
+                queue.push_back(next);
+            }
+        }
+    }
+
+    table
+}
+
+/// For each corner and edge, by its home position, the number of quarter turns needed to bring it
+/// home ignoring every other piece, indexed by position times the number of orientations plus
+/// orientation.
+struct PieceTables {
+    corners: Vec<Vec<u8>>,
+    edges: Vec<Vec<u8>>,
+}
+
+/// Returns the distance table of each piece of one kind by a breadth first search from its home.
+/// moves holds the permutation and orientations, in the format of RubiksCube::corner_permutation
+/// or edge_permutation, of the cube each quarter turn takes solved to.
+fn piece_distances(moves: &[(Vec<u8>, Vec<u8>)], num_orientations: usize) -> Vec<Vec<u8>> {
+    let num_positions = moves[0].0.len();
+
+    (0..num_positions)
+        .map(|home| {
+            let mut distances = vec![u8::MAX; num_positions * num_orientations];
+            distances[home * num_orientations] = 0;
+            let mut queue = VecDeque::from([(home, 0)]);
+
+            while let Some((position, orientation)) = queue.pop_front() {
+                let distance = distances[position * num_orientations + orientation];
+                for (permutation, orientations) in moves {
+                    // The move takes the piece in position to the position that lists it.
+                    let to = permutation.iter().position(|from| *from as usize == position).unwrap();
+                    let next = (to, (orientation + orientations[to] as usize) % num_orientations);
+
+                    let idx = next.0 * num_orientations + next.1;
+                    if distances[idx] == u8::MAX {
+                        distances[idx] = distance + 1;
+                        queue.push_back(next);
+                    }
+                }
+            }
+
+            distances
+        })
+        .collect()
+}
+
+fn build_piece_tables() -> PieceTables {
+    let turned: Vec<RubiksCube> = Rotation::all().iter()
+        .map(|rotation| {
+            let mut cube = RubiksCube::solved();
+            cube.apply(rotation);
+            cube
+        })
+        .collect();
+    let corner_moves: Vec<_> = turned.iter()
+        .map(|cube| (cube.corner_permutation().to_vec(), cube.corner_orientations().to_vec()))
+        .collect();
+    let edge_moves: Vec<_> = turned.iter()
+        .map(|cube| (cube.edge_permutation().to_vec(), cube.edge_orientations().to_vec()))
+        .collect();
+
+    PieceTables { corners: piece_distances(&corner_moves, 3), edges: piece_distances(&edge_moves, 2) }
+}
+
+/// Returns the sum over every piece of the distance tables gives for it.
+fn distance_sum(tables: &[Vec<u8>], permutation: &[u8], orientations: &[u8]) -> usize {
+    let num_orientations = tables[0].len() / permutation.len();
+    permutation.iter()
+        .zip(orientations)
+        .enumerate()
+        .map(|(position, (piece, orientation))| {
+            tables[*piece as usize][position * num_orientations + *orientation as usize] as usize
+        })
+        .sum()
+}
+
+/// Builds any tables that have not been built yet. Solvers should call this before searching so
+/// the one time cost isn't paid partway through a search.
+pub fn build_tables() {
+    CORNER_TABLE.get_or_init(build_corner_table);
+    PIECE_TABLES.get_or_init(build_piece_tables);
+}
+
+/// Returns the minimum number of quarter turns needed to orient every corner of cube, which is a
+/// lower bound on the number needed to solve it.
+pub fn corner_heuristic(cube: &RubiksCube) -> u8 {
+    CORNER_TABLE.get_or_init(build_corner_table)[corner_orientation_index(cube)]
+}
+
+impl <'a> RubiksCube<'a> {
+    /// Returns a lower bound on the number of quarter turns needed to solve self, the largest of
+    /// the bounds given by each pattern database. A solved cube is 0.
+    pub fn estimated_distance(&self) -> u8 {
+        HEURISTICS.iter()
+            .map(|heuristic| heuristic(self))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns a lower bound on the number of quarter turns needed to solve self from how far each
+    /// piece is from home. Each quarter turn brings at most four corners and four edges one turn
+    /// closer, so it is the larger of the corner and edge sums divided by four, rounded up. A
+    /// solved cube is 0.
+    pub fn lower_bound(&self) -> u8 {
+        let tables = PIECE_TABLES.get_or_init(build_piece_tables);
+        let corners = distance_sum(&tables.corners, &self.corner_permutation(), &self.corner_orientations());
+        let edges = distance_sum(&tables.edges, &self.edge_permutation(), &self.edge_orientations());
+
+        corners.div_ceil(PIECES_PER_TURN).max(edges.div_ceil(PIECES_PER_TURN)) as u8
+    }
+
+    /// Scrambles self min_depth rotations at a time until estimated_distance is at least min_depth,
+    /// returning every rotation applied. Since the estimate is a lower bound, the result is at
+    /// least that many quarter turns from solved.
+    ///
+    /// States with a high estimate are rare, so this gets slow quickly as min_depth grows: an
+    /// estimate of 9 turns up about once in a hundred scrambles and 10 about once in ten thousand.
+    /// No state has an estimate above 26, the most quarter turns any state needs, so a larger
+    /// min_depth never returns.
+    pub fn scramble_min_depth(&mut self, rng: &mut impl Rng, min_depth: usize) -> Vec<Rotation> {
+        let mut result = Vec::new();
+        while (self.estimated_distance() as usize) < min_depth {
+            result.extend(self.scramble(rng, min_depth));
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use super::*;
+    use crate::model::coord_cube::CoordCube;
+    use crate::model::rotation::{count_metric, Metric};
+
+    #[test]
+    fn corner_heuristic_is_a_lower_bound() {
+        assert_eq!(corner_heuristic(&RubiksCube::solved()), 0);
+
+        for seed in 0..10 {
+            let mut cube = RubiksCube::solved();
+            cube.scramble_seeded(seed, 4);
+            assert!(corner_heuristic(&cube) as usize <= cube.solve().len());
+        }
+    }
+
+    #[test]
+    fn estimated_distance_is_zero_when_solved_and_at_most_one_after_a_turn() {
+        assert_eq!(RubiksCube::solved().estimated_distance(), 0);
+
+        for rotation in Rotation::all() {
+            let mut cube = RubiksCube::solved();
+            cube.turn(&rotation);
+            assert!(cube.estimated_distance() <= 1);
+        }
+    }
+
+    #[test]
+    fn scramble_min_depth_reaches_the_depth() {
+        let mut rng = StdRng::seed_from_u64(572);
+        for _ in 0..5 {
+            let mut cube = RubiksCube::solved();
+            let rotations = cube.scramble_min_depth(&mut rng, 4);
+            assert!(cube.estimated_distance() >= 4);
+            assert!(rotations.len() >= 4);
+        }
+    }
+
+    #[test]
+    fn scramble_min_depth_goes_past_the_corner_table() {
+        let corner_max = *CORNER_TABLE.get_or_init(build_corner_table).iter().max().unwrap() as usize;
+
+        let mut rng = StdRng::seed_from_u64(5720);
+        let mut cube = RubiksCube::solved();
+        cube.scramble_min_depth(&mut rng, corner_max + 1);
+        assert!(cube.estimated_distance() as usize > corner_max);
+    }
+
+    #[test]
+    fn lower_bound_never_exceeds_real_solutions() {
+        assert_eq!(RubiksCube::solved().lower_bound(), 0);
+
+        for seed in 0..10 {
+            let mut cube = RubiksCube::solved();
+            cube.scramble_seeded(seed, 4);
+            let length = cube.solve().len();
+            assert!(cube.lower_bound() as usize <= length);
+            assert!(cube.estimated_distance() >= cube.lower_bound());
+            assert!(cube.estimated_distance() as usize <= length);
+        }
+
+        for seed in 0..10 {
+            let mut cube = RubiksCube::solved();
+            cube.scramble_seeded(seed, 40);
+            let solution = CoordCube::from_cube(&cube).solve_two_phase(24).unwrap();
+            assert!(cube.lower_bound() as usize <= count_metric(&solution, Metric::QuarterTurn));
+        }
+    }
+}
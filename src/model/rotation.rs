@@ -1,16 +1,20 @@
+use std::collections::HashSet;
 use std::fmt::{Display, Formatter, Error};
 use std::result::Result;
+use std::str::FromStr;
 use rand::Rng;
 
-use super::color::{Color, NUM_COLORS, ALL_COLORS};
+use super::color::{Color, NUM_COLORS, ALL_COLORS, WHITE, YELLOW, RED, ORANGE, BLUE, GREEN};
+use super::rubiks_cube::whole_cube_turn;
 
-#[derive(Eq, PartialEq, Clone)]
+#[derive(Hash, Eq, PartialEq, Clone, Debug)]
 pub enum Direction {
     Clockwise = 0,
     CounterClockwise = 1,
+    Double = 2,
 }
 
-#[derive(Clone)]
+#[derive(Hash, Eq, PartialEq, Clone, Debug)]
 pub struct Rotation {
     pub face: &'static Color,
     pub direction: Direction,
@@ -21,12 +25,145 @@ impl Display for Rotation {
         let direction_str= match self.direction {
             Direction::Clockwise => "",
             Direction::CounterClockwise => "'",
+            Direction::Double => "2",
         };
         write!(f, "{}{}", self.face.abrv, direction_str)
     }
 }
 
+/// The reasons a rotation or sequence of rotations could not be parsed. Each carries the offending
+/// token.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum ParseError {
+    /// The token does not start with a face letter.
+    UnknownFace(String),
+    /// The token's suffix is not empty, ' or 2.
+    UnknownDirection(String),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            ParseError::UnknownFace(token) => write!(f, "unknown face in \"{}\"", token),
+            ParseError::UnknownDirection(token) => write!(f, "unknown direction in \"{}\"", token),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The letters a sequence of rotations names faces with.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Notation {
+    /// The abbreviation of each face's color, as Display writes them, e.g. "w r' b2".
+    ColorLetters,
+    /// The usual U/R/F/D/L/B letters, e.g. "U F' R2". These are translated with white as U, red as
+    /// R, green as F, orange as L, blue as B and yellow as D, which is holding the cube with white
+    /// on top and red on the right. The built in algorithms are written this way.
+    Standard,
+}
+
+impl Notation {
+    pub(crate) fn face(&self, letter: &str) -> Option<&'static Color> {
+        match self {
+            Notation::ColorLetters => Color::from_abrv(letter),
+            Notation::Standard => match letter {
+                "U" => Some(&WHITE),
+                "D" => Some(&YELLOW),
+                "F" => Some(&GREEN),
+                "B" => Some(&BLUE),
+                "R" => Some(&RED),
+                "L" => Some(&ORANGE),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Parses a single rotation, a face letter followed by nothing, ' or 2, returning it along with
+/// whether it is a wide turn, written with a w after the face, e.g. "rw'". A wide turn is returned
+/// as the turn of the face named.
+fn parse_token(s: &str, notation: Notation) -> Result<(Rotation, bool), ParseError> {
+    let split = s.char_indices().nth(1).map_or(s.len(), |(i, _)| i);
+    let (face, suffix) = s.split_at(split);
+
+    let face = notation.face(face)
+        .ok_or_else(|| ParseError::UnknownFace(s.to_string()))?;
+    let (wide, suffix) = match suffix.strip_prefix('w') {
+        Some(suffix) => (true, suffix),
+        None => (false, suffix),
+    };
+    let direction = match suffix {
+        "" => Direction::Clockwise,
+        "'" => Direction::CounterClockwise,
+        "2" => Direction::Double,
+        _ => return Err(ParseError::UnknownDirection(s.to_string())),
+    };
+
+    Ok((Rotation { face, direction }, wide))
+}
+
+/// Parses a single rotation in the format produced by Display, e.g. "w", "r'" or "b2". A wide turn
+/// such as "rw'" is parsed as the turn of the opposite face, which is the same up to a whole-cube
+/// rotation. See RubiksCube::turn_wide.
+impl FromStr for Rotation {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (rotation, wide) = parse_token(s, Notation::ColorLetters)?;
+        match wide {
+            true => Ok(Rotation { face: rotation.face.opposite(), direction: rotation.direction }),
+            false => Ok(rotation),
+        }
+    }
+}
+
+/// Parses a sequence of rotations separated by whitespace and/or commas, e.g. "w r' b2".
+pub fn parse_sequence(input: &str) -> Result<Vec<Rotation>, ParseError> {
+    parse_sequence_with(input, Notation::ColorLetters)
+}
+
+/// Parses a sequence of rotations written in notation, separated by whitespace and/or commas.
+/// parse_sequence_with("R U R' U'", Notation::Standard) gives the same rotations as
+/// parse_sequence("r w r' w'").
+///
+/// A wide turn is parsed as the turn of the opposite face, as in RubiksCube::turn_wide. The two
+/// differ by a whole-cube rotation, which moves every face but the two turned, so the letters after
+/// a wide turn name the faces that are now where those letters point: "bw w" gives the same
+/// rotations as "g r".
+pub fn parse_sequence_with(input: &str, notation: Notation) -> Result<Vec<Rotation>, ParseError> {
+    // The face of the model at the position each letter names, indexed the same as ALL_COLORS.
+    let mut frame = ALL_COLORS;
+    let mut result = Vec::new();
+
+    for token in input.split(|c: char| c.is_whitespace() || c == ',').filter(|token| !token.is_empty()) {
+        let (rotation, wide) = parse_token(token, notation)?;
+        let face = frame[rotation.face.idx];
+        if !wide {
+            result.push(Rotation { face, direction: rotation.direction });
+            continue;
+        }
+
+        result.push(Rotation { face: face.opposite(), direction: rotation.direction.clone() });
+        // The whole cube turns the same way as the named face, carrying each face to the position
+        // the turn moves its own.
+        frame = whole_cube_turn(&rotation).map(|position| frame[position.idx]);
+    }
+
+    Ok(result)
+}
+
 impl Rotation {
+    /// Returns every quarter turn of every face: clockwise then counterclockwise for each color in
+    /// the order of ALL_COLORS. Searches expand moves in this order, which keeps their results
+    /// the same from run to run.
+    pub fn all() -> Vec<Rotation> {
+        ALL_COLORS.iter()
+            .flat_map(|face| [Direction::Clockwise, Direction::CounterClockwise]
+                .map(|direction| Rotation { face, direction }))
+            .collect()
+    }
+
     pub fn random(rng: &mut impl Rng) -> Self {
         let i: usize = rng.gen_range(0..NUM_COLORS * 2);
     
@@ -34,6 +171,304 @@ impl Rotation {
             i if i % 2 == 0 => Direction::Clockwise,
             _ => Direction::CounterClockwise
         };
-        Rotation { face: ALL_COLORS[i / 2], direction: direction }
+        Rotation { face: ALL_COLORS[i / 2], direction }
+    }
+
+    /// Returns the rotation that does to RubiksCube::mirror of a cube what self does to the cube:
+    /// the same turn of blue and green swapped, in the opposite direction. Doubles keep their
+    /// direction.
+    pub fn mirror(&self) -> Rotation {
+        let face = match self.face {
+            face if face == &BLUE => &GREEN,
+            face if face == &GREEN => &BLUE,
+            face => face,
+        };
+        Rotation { face, direction: self.inverse().direction }
+    }
+
+    /// Returns the rotation that undoes self. Doubles are their own inverse.
+    pub fn inverse(&self) -> Rotation {
+        let direction = match self.direction {
+            Direction::Clockwise => Direction::CounterClockwise,
+            Direction::CounterClockwise => Direction::Clockwise,
+            Direction::Double => Direction::Double,
+        };
+        Rotation { face: self.face, direction }
+    }
+}
+
+/// Returns a random scramble of length rotations, written as Display writes them and separated by
+/// spaces. Like the scrambles used in competition, it includes doubles, never turns the same face
+/// twice in a row and never turns both faces of an axis and then the first again, since those
+/// moves would merge or commute into a shorter scramble.
+pub fn generate_wca_scramble(rng: &mut impl Rng, length: usize) -> String {
+    let mut result: Vec<Rotation> = Vec::new();
+    while result.len() < length {
+        let face = ALL_COLORS[rng.gen_range(0..NUM_COLORS)];
+        let redundant = match result[..] {
+            [.., ref last] if last.face == face => true,
+            [.., ref before, ref last] => last.face == face.opposite() && before.face == face,
+            _ => false,
+        };
+        if redundant {
+            continue;
+        }
+
+        let direction = match rng.gen_range(0..3) {
+            0 => Direction::Clockwise,
+            1 => Direction::CounterClockwise,
+            _ => Direction::Double,
+        };
+        result.push(Rotation { face, direction });
+    }
+
+    format_sequence(&result)
+}
+
+/// Writes rotations as Display writes them, separated by spaces. parse_sequence reads the result
+/// back.
+pub fn format_sequence(rotations: &[Rotation]) -> String {
+    rotations.iter()
+        .map(|rotation| rotation.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Writes rotations like format_sequence, but with at most per_line on each line and every
+/// rotation padded to the same width so the columns line up. parse_sequence reads the result back.
+///
+/// Panics if per_line is 0.
+pub fn format_sequence_wrapped(rotations: &[Rotation], per_line: usize) -> String {
+    let names: Vec<String> = rotations.iter().map(|rotation| rotation.to_string()).collect();
+    let width = names.iter().map(|name| name.len()).max().unwrap_or(0);
+
+    names.chunks(per_line)
+        .map(|line| {
+            line.iter()
+                .map(|name| format!("{:<width$}", name))
+                .collect::<Vec<_>>()
+                .join(" ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The ways of counting the length of a sequence of rotations.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Metric {
+    /// Half Turn Metric: every face turn counts as 1, including doubles.
+    HalfTurn,
+    /// Quarter Turn Metric: a double counts as 2.
+    QuarterTurn,
+    /// Slice Turn Metric: slice moves count as 1. Slice moves are not representable as a Rotation,
+    /// so this currently agrees with HalfTurn.
+    SliceTurn,
+}
+
+/// Returns the length of the specified rotations when counted using metric.
+pub fn count_metric(moves: &[Rotation], metric: Metric) -> usize {
+    moves.iter()
+        .map(|rotation| match (metric, &rotation.direction) {
+            (Metric::QuarterTurn, Direction::Double) => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+/// A summary of how a solution compares to the scramble it solves, with lengths in the Half Turn
+/// Metric.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct SolutionStats {
+    pub scramble_length: usize,
+    pub solution_length: usize,
+    /// Whether the solution is shorter than undoing the scramble one rotation at a time.
+    pub improves_on_inverse: bool,
+}
+
+/// Compares the length of solution to that of scramble. Does not check that solution actually
+/// solves the scrambled cube; see RubiksCube::verify_solution for that.
+pub fn analyze_solution(scramble: &[Rotation], solution: &[Rotation]) -> SolutionStats {
+    let scramble_length = count_metric(scramble, Metric::HalfTurn);
+    let solution_length = count_metric(solution, Metric::HalfTurn);
+    SolutionStats { scramble_length, solution_length, improves_on_inverse: solution_length < scramble_length }
+}
+
+/// Returns moves with each run of turns of the same face merged into one turn, or none if the run
+/// cancels out. This is the same cube state with no more rotations than moves.
+pub fn simplify(moves: &[Rotation]) -> Vec<Rotation> {
+    let quarter_turns = |direction: &Direction| match direction {
+        Direction::Clockwise => 1,
+        Direction::CounterClockwise => 3,
+        Direction::Double => 2,
+    };
+
+    let mut result: Vec<Rotation> = Vec::new();
+    for rotation in moves {
+        let mut total = quarter_turns(&rotation.direction);
+        if let Some(last) = result.last().filter(|last| last.face == rotation.face) {
+            total += quarter_turns(&last.direction);
+            result.pop();
+        }
+
+        let direction = match total % 4 {
+            0 => continue,
+            1 => Direction::Clockwise,
+            2 => Direction::Double,
+            _ => Direction::CounterClockwise,
+        };
+        result.push(Rotation { face: rotation.face, direction });
+    }
+
+    result
+}
+
+/// Returns the simplified form of moves with every run of turns of two opposite faces, which
+/// commute, ordered as the faces are in ALL_COLORS. Sequences that differ only by cancelling turns
+/// or by the order of commuting turns have the same canonical form.
+pub fn canonicalize_sequence(moves: &[Rotation]) -> Vec<Rotation> {
+    let mut result = simplify(moves);
+    loop {
+        let mut sorted = result.clone();
+        for run in sorted.chunk_by_mut(|a, b| a.face == b.face || a.face.opposite() == b.face) {
+            run.sort_by_key(|rotation| rotation.face.idx);
+        }
+
+        // Sorting can bring turns of the same face together, which then merge.
+        let next = simplify(&sorted);
+        if next == result {
+            return result;
+        }
+        result = next;
+    }
+}
+
+/// Returns solutions without repeats, keeping the first of each. If simplify_first is true, each
+/// solution is passed through simplify before comparing, so e.g. "w w" and "w2" count as the same
+/// and the simplified form is what's returned.
+pub fn dedup_solutions(solutions: Vec<Vec<Rotation>>, simplify_first: bool) -> Vec<Vec<Rotation>> {
+    let mut seen = HashSet::new();
+    solutions.into_iter()
+        .map(|solution| if simplify_first { simplify(&solution) } else { solution })
+        .filter(|solution| seen.insert(solution.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use super::*;
+
+    fn parse(notation: &str) -> Vec<Rotation> {
+        parse_sequence(notation).unwrap()
+    }
+
+    #[test]
+    fn count_metric_counts_doubles_by_metric() {
+        let moves = parse("w r2 b' o2 g");
+        assert_eq!(count_metric(&moves, Metric::HalfTurn), 5);
+        assert_eq!(count_metric(&moves, Metric::QuarterTurn), 7);
+        assert_eq!(count_metric(&moves, Metric::SliceTurn), 5);
+    }
+
+    #[test]
+    fn rotations_compare_by_face_and_direction() {
+        assert_eq!(parse("w r' b2"), parse("w r' b2"));
+        assert_ne!(parse("w r' b2"), parse("w r b2"));
+        assert_ne!(parse("w r' b2"), parse("w o' b2"));
+    }
+
+    #[test]
+    fn analyze_solution_compares_to_the_scramble() {
+        let scramble = parse("w r b'");
+        let inverse = parse("b r' w'");
+        let stats = analyze_solution(&scramble, &inverse);
+        assert_eq!(stats, SolutionStats { scramble_length: 3, solution_length: 3, improves_on_inverse: false });
+
+        assert!(analyze_solution(&scramble, &parse("b w'")).improves_on_inverse);
+    }
+
+    #[test]
+    fn equal_rotations_collapse_in_a_hash_set() {
+        let set: HashSet<Rotation> = parse("w r' w b2 r' w").into_iter().collect();
+        assert_eq!(set.len(), 3);
+        assert!(set.contains(&parse("b2")[0]));
+    }
+
+    #[test]
+    fn inverse_is_an_involution() {
+        for rotation in parse("w w' w2 r r' r2 b b' b2 o o' o2 g g' g2 y y' y2") {
+            assert_eq!(rotation.inverse().face, rotation.face);
+            assert_eq!(rotation.inverse().inverse(), rotation);
+        }
+    }
+
+    #[test]
+    fn simplify_merges_runs_of_the_same_face() {
+        assert_eq!(simplify(&parse("w w r' r b2 b")), parse("w2 b'"));
+        assert_eq!(simplify(&parse("w r r' w'")), Vec::new());
+    }
+
+    #[test]
+    fn dedup_solutions_removes_duplicates() {
+        let solutions = vec![parse("w r"), parse("w r"), parse("w w r"), parse("w2 r")];
+        assert_eq!(dedup_solutions(solutions.clone(), false), vec![parse("w r"), parse("w w r"), parse("w2 r")]);
+        assert_eq!(dedup_solutions(solutions, true), vec![parse("w r"), parse("w2 r")]);
+    }
+
+    #[test]
+    fn wide_turns_move_the_faces_later_letters_name() {
+        assert_eq!(parse("bw w"), parse("g r"));
+        assert_eq!(parse("bw b' w"), parse("g b' r"));
+        assert_eq!(parse_sequence_with("Rw U", Notation::Standard).unwrap(), parse("o g"));
+        assert_eq!(parse("bw bw' w"), parse("g g' w"));
+        assert_eq!("bw".parse::<Rotation>().unwrap(), parse("g")[0]);
+    }
+
+    #[test]
+    fn wca_scrambles_parse_back_without_redundant_moves() {
+        let mut rng = StdRng::seed_from_u64(588);
+        for _ in 0..20 {
+            let scramble = parse(&generate_wca_scramble(&mut rng, 25));
+            assert_eq!(scramble.len(), 25);
+            for window in scramble.windows(2) {
+                assert_ne!(window[0].face, window[1].face);
+            }
+            for window in scramble.windows(3) {
+                assert!(window[1].face != window[0].face.opposite() || window[2].face != window[0].face);
+            }
+        }
+    }
+
+    #[test]
+    fn both_notations_give_the_same_rotations() {
+        let standard = parse_sequence_with("U F' R2 B D L' Uw", Notation::Standard).unwrap();
+        assert_eq!(standard, parse("w g' r2 b y o' y"));
+        assert_eq!(parse_sequence_with("w g' r2 b y o' ww", Notation::ColorLetters).unwrap(), standard);
+    }
+
+    #[test]
+    fn canonicalize_sequence_orders_only_commuting_turns() {
+        assert_eq!(canonicalize_sequence(&parse("b g")), canonicalize_sequence(&parse("g b")));
+        assert_eq!(canonicalize_sequence(&parse("b w")), parse("b w"));
+        assert_eq!(canonicalize_sequence(&parse("w b")), parse("w b"));
+        assert_eq!(canonicalize_sequence(&parse("b g b' r")), parse("g r"));
+    }
+
+    #[test]
+    fn format_sequence_wrapped_wraps_and_parses_back() {
+        let moves: Vec<Rotation> = (0..25).map(|i| {
+            let direction = [Direction::Clockwise, Direction::CounterClockwise, Direction::Double][i % 3].clone();
+            Rotation { face: ALL_COLORS[i % NUM_COLORS], direction }
+        }).collect();
+
+        let wrapped = format_sequence_wrapped(&moves, 8);
+        let lines: Vec<&str> = wrapped.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[..3].iter().all(|line| parse(line).len() == 8));
+        assert_eq!(parse(&wrapped), moves);
     }
 }
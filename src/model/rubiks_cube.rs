@@ -1,56 +1,361 @@
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::{Display, Formatter, Error};
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::result::Result;
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use rand::Rng;
-use queues::{Queue, IsQueue};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
 
 use super::block::BlockFace;
-use super::color::{Color, WHITE, RED, BLUE, ORANGE, GREEN, YELLOW, NUM_COLORS, ALL_COLORS};
-use super::rotation::{Rotation, Direction};
+use super::color::{Color, ColorScheme, WESTERN, WHITE, RED, BLUE, ORANGE, GREEN, YELLOW, NUM_COLORS, ALL_COLORS, NUM_NEIGHBORS};
+use super::rotation::{parse_sequence, ParseError, Rotation, Direction};
 use super::block::Block;
 
-const NUM_NEIGHBORS: usize = 4;
-const SIDE_LEN: usize = 3;
-
-// Stored in the order Top, Right, Bottom, Left. 
-const ADJACENT_COLORS: [[&'static Color; NUM_NEIGHBORS]; NUM_COLORS] = [
-    [&GREEN, &ORANGE, &BLUE, &RED], // White
-    [&WHITE, &BLUE, &YELLOW, &GREEN], // Red
-    [&WHITE, &ORANGE, &YELLOW, &RED], // Blue
-    [&WHITE, &GREEN, &YELLOW, &BLUE], // Orange
-    [&WHITE, &RED, &YELLOW, &ORANGE], // Green
-    [&BLUE, &ORANGE, &GREEN, &RED] // Yellow
-];
+pub(crate) const SIDE_LEN: usize = 3;
+const NUM_FACELETS: usize = NUM_COLORS * SIDE_LEN * SIDE_LEN;
+// Where the edge and corner stickers sit in a face_colors grid. Both go Top, Right, Bottom, Left.
+const EDGE_CELLS: [(usize, usize); NUM_NEIGHBORS] = [(0, 1), (1, 2), (2, 1), (1, 0)];
+const CORNER_CELLS: [(usize, usize); NUM_NEIGHBORS] = [(0, 2), (2, 2), (2, 0), (0, 0)];
+// Any state can be solved in 20 face turns. Restricted searches stop there too, since a move set
+// that needs longer is better served by a phased solver.
+const MAX_RESTRICTED_DEPTH: usize = 20;
+// How many states solve_with_timeout expands between looks at the clock.
+const TIMEOUT_CHECK_INTERVAL: usize = 1024;
 
 /// Constructs and returns an array such that for two colors a and b, iff arr[a.idx] == Some(b) then
 /// a rotates to b in the specified rotation. The index of the color opposite face will be None in
 /// the resulting array.
-fn get_color_rotations(rotation: &Rotation) -> [Option<&'static Color>; NUM_COLORS] {
+pub(crate) fn get_color_rotations(rotation: &Rotation) -> [Option<&'static Color>; NUM_COLORS] {
     let face = rotation.face;
-    let adjacent = ADJACENT_COLORS[face.idx];
-    let faces_in_order: [&'static Color; NUM_NEIGHBORS] = match rotation.direction {
-        Direction::Clockwise => adjacent,
-        Direction::CounterClockwise => {
-            let mut result = [ &WHITE; NUM_NEIGHBORS ];
-            for i in 0..NUM_NEIGHBORS {
-                result[i] = adjacent[NUM_NEIGHBORS - i - 1];
-            }
-            result
-        },
+    let adjacent = face.adjacent();
+    let step = match rotation.direction {
+        Direction::Clockwise => 1,
+        Direction::CounterClockwise => NUM_NEIGHBORS - 1,
+        Direction::Double => 2,
     };
 
     let mut result = [None; NUM_COLORS];
     result[face.idx] = Some(face);
-    for i in 0..faces_in_order.len() - 1 {
-        result[faces_in_order[i].idx] = Some(faces_in_order[i + 1]);
+    for i in 0..NUM_NEIGHBORS {
+        result[adjacent[i].idx] = Some(adjacent[(i + step) % NUM_NEIGHBORS]);
+    }
+    result
+}
+
+/// Returns, for each position indexed the same as ALL_COLORS, the face that turning the whole cube
+/// the same way as rotation carries to it.
+pub(crate) fn whole_cube_turn(rotation: &Rotation) -> [&'static Color; NUM_COLORS] {
+    let rotations = get_color_rotations(rotation);
+    let mut result = ALL_COLORS;
+    for face in ALL_COLORS {
+        result[rotations[face.idx].unwrap_or(face).idx] = face;
+    }
+    result
+}
+
+/// Returns the faces of each corner position in the order solved() builds the corners: the white
+/// or yellow face followed by the two side faces in clockwise order.
+pub(crate) fn corner_positions() -> Vec<[&'static Color; 3]> {
+    let mut result = Vec::new();
+    for color in [&WHITE, &YELLOW] {
+        let neighbors = color.adjacent();
+        for i in 0..NUM_NEIGHBORS {
+            result.push([color, neighbors[i], neighbors[(i + 1) % NUM_NEIGHBORS]]);
+        }
     }
-    result[faces_in_order[faces_in_order.len() - 1].idx] = Some(faces_in_order[0]);
     result
 }
 
+/// Returns the faces of each edge position in the order solved() builds the edges.
+pub(crate) fn edge_positions() -> Vec<[&'static Color; 2]> {
+    let mut result = Vec::new();
+    for color in [&WHITE, &YELLOW] {
+        for neighbor in color.adjacent() {
+            result.push([color, neighbor]);
+        }
+    }
+    for color in [&GREEN, &BLUE] {
+        for neighbor in color.adjacent() {
+            if neighbor != &WHITE && neighbor != &YELLOW {
+                result.push([color, neighbor]);
+            }
+        }
+    }
+    result
+}
+
+/// Returns the white or yellow color among the colors of an edge if there is one and the red or
+/// orange one otherwise. Every edge has exactly one such color.
+fn edge_reference(colors: [&Color; 2]) -> &Color {
+    colors.iter()
+        .find(|color| **color == &WHITE || **color == &YELLOW)
+        .or_else(|| colors.iter().find(|color| **color == &RED || **color == &ORANGE))
+        .expect("Every edge has a white, yellow, red or orange sticker")
+}
+
+/// A whole-cube rotation, as a map from each color to the color of the face it is moved onto.
+pub(crate) type Symmetry = [&'static Color; NUM_COLORS];
+
+static SYMMETRIES: OnceLock<Vec<Symmetry>> = OnceLock::new();
+
+/// Returns the quarter turn of the whole cube about the axis through face that moves each of its
+/// neighbors onto the next one.
+fn quarter_symmetry(face: &Color) -> Symmetry {
+    let mut result = ALL_COLORS;
+    let adjacent = face.adjacent();
+    for i in 0..NUM_NEIGHBORS {
+        result[adjacent[i].idx] = adjacent[(i + 1) % NUM_NEIGHBORS];
+    }
+    result
+}
+
+/// Returns the 24 rotations of the whole cube, starting with the identity.
+pub(crate) fn symmetries() -> &'static [Symmetry] {
+    SYMMETRIES.get_or_init(|| {
+        let generators = [quarter_symmetry(&WHITE), quarter_symmetry(&RED)];
+        let mut result = vec![ALL_COLORS];
+        let mut i = 0;
+        while i < result.len() {
+            for generator in generators.iter() {
+                let composed = result[i].map(|color| generator[color.idx]);
+                if !result.contains(&composed) {
+                    result.push(composed);
+                }
+            }
+            i += 1;
+        }
+        result
+    })
+}
+
+/// Returns the white or yellow color of a block, which names the layer it belongs in, or None if it
+/// belongs in the middle layer.
+fn layer_of<'a>(block: &Block<'a>) -> Option<&'a Color> {
+    block.colors().into_iter()
+        .find(|color| *color == &WHITE || *color == &YELLOW)
+}
+
+/// Returns a number that is unique to the set of colors on a block.
+fn color_set_key<'a>(colors: impl Iterator<Item = &'a Color>) -> usize {
+    colors.fold(0, |acc, color| acc | 1 << color.idx)
+}
+
+/// Returns the sticker that faces shows on face for the block between face and the faces in others,
+/// where faces is indexed by color and laid out like face_colors.
+fn facelet<'a>(
+    faces: &[[[&'a Color; SIDE_LEN]; SIDE_LEN]; NUM_COLORS],
+    face: &Color,
+    others: &[&Color]
+) -> &'a Color {
+    let neighbors = face.adjacent();
+    let (row, col) = (0..NUM_NEIGHBORS)
+        .find_map(|i| match others {
+            [a] if *a == neighbors[i] => Some(EDGE_CELLS[i]),
+            [_, _] if others.contains(&neighbors[i])
+                && others.contains(&neighbors[(i + 1) % NUM_NEIGHBORS]) => Some(CORNER_CELLS[i]),
+            _ => None,
+        })
+        .expect("Every block's faces are adjacent");
+    faces[face.idx][row][col]
+}
+
+/// Returns whether permutation is made of an odd number of swaps.
+fn is_odd(permutation: &[u8]) -> bool {
+    let mut inversions = 0;
+    for i in 0..permutation.len() {
+        for j in i + 1..permutation.len() {
+            if permutation[i] > permutation[j] {
+                inversions += 1;
+            }
+        }
+    }
+    inversions % 2 == 1
+}
+
+/// The reasons a set of stickers passed to RubiksCube::from_facelets is not a real cube.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum FaceletError {
+    /// A facelet string had this many stickers instead of 54.
+    WrongLength(usize),
+    /// A facelet string had this character, which is not a color abbreviation.
+    UnknownColor(char),
+    /// The center of the face with this abbreviation is a different color.
+    WrongCenter(String),
+    /// A block has these stickers, which no piece of the cube has.
+    UnknownPiece(String),
+    /// More than one block has these stickers.
+    DuplicatePiece(String),
+    /// The corners are twisted in a way no sequence of turns can produce.
+    TwistedCorner,
+    /// The edges are flipped in a way no sequence of turns can produce.
+    FlippedEdge,
+    /// Exactly two pieces are swapped, which no sequence of turns can produce.
+    ParityMismatch,
+}
+
+impl Display for FaceletError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            FaceletError::WrongLength(len) => write!(f, "expected {} stickers but found {}", NUM_FACELETS, len),
+            FaceletError::UnknownColor(c) => write!(f, "unknown color '{}'", c),
+            FaceletError::WrongCenter(face) => write!(f, "the center of face {} is the wrong color", face),
+            FaceletError::UnknownPiece(stickers) => write!(f, "no piece has the stickers {}", stickers),
+            FaceletError::DuplicatePiece(stickers) => write!(f, "more than one piece has the stickers {}", stickers),
+            FaceletError::TwistedCorner => write!(f, "a corner is twisted"),
+            FaceletError::FlippedEdge => write!(f, "an edge is flipped"),
+            FaceletError::ParityMismatch => write!(f, "two pieces are swapped"),
+        }
+    }
+}
+
+impl std::error::Error for FaceletError {}
+
+/// A snapshot of a search in progress, yielded by RubiksCube::solve_iter.
+#[derive(Clone, Debug)]
+pub struct SolveProgress {
+    /// The number of rotations in the paths currently being searched.
+    pub depth: usize,
+    /// The number of states taken off the queue so far.
+    pub nodes_expanded: usize,
+    /// Only set on the final item, to the same solution solve would return.
+    pub solution: Option<Vec<Rotation>>,
+}
+
+/// The result of RubiksCube::solve_reported: a solution along with how much work finding it took.
+#[derive(Clone, Debug)]
+pub struct SolveReport {
+    /// The same solution solve would return.
+    pub solution: Vec<Rotation>,
+    /// The number of states taken off the queue, including the solved one.
+    pub nodes_expanded: usize,
+    pub elapsed: Duration,
+    /// The deepest level of the search reached, which is the length of the solution.
+    pub max_depth: usize,
+}
+
+/// A sticker that differs between two cubes, as returned by RubiksCube::diff. row and col are as in
+/// RubiksCube::face_colors.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct StickerDiff<'a> {
+    pub face: &'static Color,
+    pub row: usize,
+    pub col: usize,
+    /// The sticker's color on the cube diff was called on.
+    pub ours: &'a Color,
+    /// The sticker's color on the other cube.
+    pub theirs: &'a Color,
+}
+
+/// The result of RubiksCube::solve_with_timeout.
+#[derive(Clone, Debug)]
+pub enum SolveOutcome {
+    /// The search finished in time with the same solution solve would return.
+    Solved(Vec<Rotation>),
+    /// The search ran out of time. path leads to the state with the fewest misplaced stickers, as
+    /// counted by misplaced_count, of those expanded, which is the start if none had fewer.
+    BestEffort { path: Vec<Rotation>, misplaced: usize },
+}
+
+/// Rotations applied through RubiksCube::turn, oldest first, and rotations taken back by undo,
+/// most recently undone last.
+#[derive(Clone, Default)]
+struct History {
+    done: Vec<Rotation>,
+    undone: Vec<Rotation>,
+}
+
+/// Runs the same search as solve one depth at a time.
+struct SolveIter<'a> {
+    queue: VecDeque<(RubiksCube<'a>, Vec<Rotation>)>,
+    all_rotations: Vec<Rotation>,
+    depth: usize,
+    nodes_expanded: usize,
+    done: bool,
+}
+
+impl <'a> Iterator for SolveIter<'a> {
+    type Item = SolveProgress;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let mut solution = None;
+        while let Some((next, next_acc)) = self.queue.front() {
+            if next_acc.len() > self.depth {
+                break;
+            }
+
+            self.nodes_expanded += 1;
+            if next.is_solved() {
+                solution = Some(next_acc.clone());
+                break;
+            }
+
+            let (next, next_acc) = self.queue.pop_front().expect("Underflow");
+            for rotation in self.all_rotations.iter() {
+                let mut next_copy = next.clone();
+                let mut next_acc_copy = next_acc.clone();
+
+                next_copy.apply(rotation);
+                next_acc_copy.push(rotation.clone());
+                self.queue.push_back((next_copy, next_acc_copy));
+            }
+        }
+
+        // solve returns an empty solution if it runs out of states.
+        if solution.is_none() && self.queue.is_empty() {
+            solution = Some(Vec::new());
+        }
+
+        let progress = SolveProgress {
+            depth: self.depth,
+            nodes_expanded: self.nodes_expanded,
+            solution,
+        };
+        self.done = progress.solution.is_some();
+        self.depth += 1;
+
+        Some(progress)
+    }
+}
+
 #[derive(Clone)]
 pub struct RubiksCube<'a> {
-    blocks: Box<[Block<'a>; 20]>
+    blocks: Box<[Block<'a>; 20]>,
+    // How many of blocks are not solved. Every change to blocks keeps it up to date, so is_solved,
+    // which searches call on every state, doesn't have to look at the blocks.
+    unsolved_blocks: u8,
+    // None unless enable_history has been called, so that cubes cloned in search loops don't
+    // carry the rotations that led to them.
+    history: Option<History>,
+    // The colors Display shows. Blocks always use the Western scheme.
+    scheme: ColorScheme,
+}
+
+// Two cubes are equal if their blocks are in the same state regardless of how they got there.
+impl <'a> PartialEq for RubiksCube<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.blocks == other.blocks
+    }
+}
+
+impl <'a> Eq for RubiksCube<'a> {}
+
+impl <'a> Hash for RubiksCube<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.blocks.hash(state);
+    }
+}
+
+impl <'a> Default for RubiksCube<'a> {
+    fn default() -> Self {
+        Self::solved()
+    }
 }
 
 impl <'a> RubiksCube<'a> {
@@ -61,7 +366,7 @@ impl <'a> RubiksCube<'a> {
         let mut idx = 0;
 
         for color in [&WHITE, &YELLOW] {
-            let neighbors = ADJACENT_COLORS[color.idx];
+            let neighbors = color.adjacent();
 
             for i in 0..neighbors.len() {
                 blocks[idx] = Block::solved_corner(color, neighbors[i], neighbors[(i + 1) % neighbors.len()]);
@@ -72,7 +377,7 @@ impl <'a> RubiksCube<'a> {
         }
 
         for color in [&GREEN, &BLUE] {
-            let neighbors = ADJACENT_COLORS[color.idx];
+            let neighbors = color.adjacent();
             for neighbor in neighbors {
                 if neighbor != &WHITE && neighbor != &YELLOW {
                     blocks[idx] = Block::solved_edge(color, neighbor);
@@ -82,88 +387,990 @@ impl <'a> RubiksCube<'a> {
         }
 
         assert!(idx == 20);
-        Self { blocks: Box::new(blocks) }
+        Self { blocks: Box::new(blocks), unsolved_blocks: 0, history: None, scheme: WESTERN }
+    }
+
+    /// Returns a solved cube that Display shows in scheme. Everything else about the cube, from
+    /// the colors passed to and returned by its methods to equality, still uses the Western scheme
+    /// that names the faces.
+    pub fn solved_with_scheme(scheme: ColorScheme) -> Self {
+        Self { scheme, ..Self::solved() }
+    }
+
+    pub fn scheme(&self) -> ColorScheme {
+        self.scheme
+    }
+
+    /// Builds the cube showing the specified stickers, where faces is indexed by color and each face
+    /// is laid out as face_colors returns it. Fails if the stickers could not come from turning a
+    /// solved cube.
+    pub fn from_facelets(faces: [[[&'a Color; SIDE_LEN]; SIDE_LEN]; NUM_COLORS]) -> Result<Self, FaceletError> {
+        if let Some(color) = ALL_COLORS.iter().find(|color| faces[color.idx][1][1] != **color) {
+            return Err(FaceletError::WrongCenter(color.abrv.to_string()));
+        }
+
+        // Each piece is stored where solved() puts it, with its stickers in the same order, so that
+        // the result equals the same state reached by turning.
+        let solved = Self::solved();
+        let mut result = Self::solved();
+        let homes: Vec<usize> = solved.blocks.iter()
+            .map(|block| color_set_key(block.colors().into_iter()))
+            .collect();
+        let mut seen = HashSet::new();
+
+        for block in solved.blocks.iter() {
+            let position = block.faces();
+            let mut stickers: Vec<BlockFace<'a>> = position.iter()
+                .map(|face| {
+                    let others: Vec<&Color> = position.iter().copied().filter(|other| other != face).collect();
+                    BlockFace { color: facelet(&faces, face, &others), face }
+                })
+                .collect();
+
+            let key = color_set_key(stickers.iter().map(|block_face| block_face.color));
+            let abrvs: String = stickers.iter().map(|block_face| block_face.color.abrv).collect();
+            let home = homes.iter().position(|home| *home == key)
+                .filter(|_| key.count_ones() as usize == stickers.len())
+                .ok_or_else(|| FaceletError::UnknownPiece(abrvs.clone()))?;
+            if !seen.insert(home) {
+                return Err(FaceletError::DuplicatePiece(abrvs));
+            }
+
+            let order = solved.blocks[home].colors();
+            stickers.sort_by_key(|block_face| order.iter().position(|color| *color == block_face.color));
+            result.blocks[home] = match &stickers[..] {
+                [a, b] => Block::Edge(a.clone(), b.clone()),
+                [a, b, c] => Block::Corner(a.clone(), b.clone(), c.clone()),
+                _ => unreachable!("Blocks have 2 or 3 stickers"),
+            };
+        }
+
+        result.count_unsolved_blocks();
+
+        if result.corner_orientations().iter().sum::<u8>() % 3 != 0 {
+            return Err(FaceletError::TwistedCorner);
+        }
+        if result.edge_orientations().iter().sum::<u8>() % 2 != 0 {
+            return Err(FaceletError::FlippedEdge);
+        }
+        if is_odd(&result.corner_permutation()) != is_odd(&result.edge_permutation()) {
+            return Err(FaceletError::ParityMismatch);
+        }
+
+        Ok(result)
+    }
+
+    /// Builds the cube whose corner_permutation, corner_orientations, edge_permutation and
+    /// edge_orientations are the ones given. Like from_facelets, each piece is stored where solved()
+    /// puts it. Does not check that the result could come from turning a solved cube.
+    pub(crate) fn from_pieces(
+        corner_permutation: &[u8; 8],
+        corner_orientations: &[u8; 8],
+        edge_permutation: &[u8; 12],
+        edge_orientations: &[u8; 12]
+    ) -> Self {
+        let solved = Self::solved();
+        let mut result = Self::solved();
+        let home = |colors: &[&Color]| solved.blocks.iter()
+            .position(|block| {
+                color_set_key(block.colors().into_iter())
+                    == color_set_key(colors.iter().copied())
+            })
+            .expect("Every piece has a home");
+
+        let corners = corner_positions();
+        for (position, (piece, orientation)) in corners.iter().zip(corner_permutation.iter().zip(corner_orientations)) {
+            // The white or yellow sticker is first in both the piece's colors and the position's
+            // faces, and the rest follow in the same clockwise order.
+            let colors = corners[*piece as usize];
+            let sticker = |k: usize| BlockFace { color: colors[k], face: position[(k + *orientation as usize) % 3] };
+            result.blocks[home(&colors)] = Block::Corner(sticker(0), sticker(1), sticker(2));
+        }
+
+        let edges = edge_positions();
+        for (position, (piece, orientation)) in edges.iter().zip(edge_permutation.iter().zip(edge_orientations)) {
+            let colors = edges[*piece as usize];
+            let reference = edge_reference(colors);
+            let reference_face = edge_reference(*position);
+            let other_face = position.iter().copied().find(|face| *face != reference_face).unwrap();
+            let sticker = |color: &'static Color| match (color == reference) == (*orientation == 0) {
+                true => BlockFace { color, face: reference_face },
+                false => BlockFace { color, face: other_face },
+            };
+            result.blocks[home(&colors)] = Block::Edge(sticker(colors[0]), sticker(colors[1]));
+        }
+
+        result.count_unsolved_blocks();
+        result
+    }
+
+    /// Parses the format written by to_facelet_string for a cube in the Western scheme, ignoring
+    /// whitespace, and builds the cube with from_facelets.
+    pub fn from_facelet_string(facelets: &str) -> Result<Self, FaceletError> {
+        Self::from_facelet_string_with_scheme(facelets, WESTERN)
+    }
+
+    /// Like from_facelet_string, but reads the colors as shown in scheme and returns a cube that
+    /// shows them the same way. Errors name colors as the Western scheme does.
+    pub fn from_facelet_string_with_scheme(facelets: &str, scheme: ColorScheme) -> Result<Self, FaceletError> {
+        let colors: Vec<&'static Color> = facelets.chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| Color::from_abrv(&c.to_string()).ok_or(FaceletError::UnknownColor(c)))
+            .map(|color| color.map(|color| scheme.face_of(color)))
+            .collect::<Result<_, _>>()?;
+        if colors.len() != NUM_FACELETS {
+            return Err(FaceletError::WrongLength(colors.len()));
+        }
+
+        let mut faces = [[[&WHITE; SIDE_LEN]; SIDE_LEN]; NUM_COLORS];
+        for (i, color) in colors.into_iter().enumerate() {
+            let sticker = i % (SIDE_LEN * SIDE_LEN);
+            faces[i / (SIDE_LEN * SIDE_LEN)][sticker / SIDE_LEN][sticker % SIDE_LEN] = color;
+        }
+
+        Ok(Self { scheme, ..Self::from_facelets(faces)? })
+    }
+
+    /// Returns the state reached by turning a solved cube through the sequence in notation, in the
+    /// format parse_sequence reads. The same string always gives the same state, which makes it the
+    /// way to share a scramble.
+    pub fn from_scramble(notation: &str) -> Result<Self, ParseError> {
+        let mut result = Self::solved();
+        parse_sequence(notation)?.iter().for_each(|rotation| result.turn(rotation));
+        Ok(result)
+    }
+
+    /// Returns the abbreviation of every sticker, as self's scheme shows it, with no separators:
+    /// each face in the order of ALL_COLORS, laid out as face_colors returns it, one row after
+    /// another.
+    pub fn to_facelet_string(&self) -> String {
+        ALL_COLORS.iter()
+            .flat_map(|face| self.get_face(face).unwrap())
+            .flatten()
+            .collect()
+    }
+
+    /// Paints the sticker at row and col of face, with rows and columns as in face_colors. Fails,
+    /// leaving self unchanged, if the stickers would no longer form a cube that turning a solved
+    /// one can reach, for the same reasons as from_facelets. Since every piece has stickers on more
+    /// than one face, a real cube only ever goes to another through several changes at once, so
+    /// this only succeeds when color is the sticker's color already; use set_stickers to paint them
+    /// together.
+    ///
+    /// Panics if row or col is not less than SIDE_LEN.
+    pub fn set_sticker(&mut self, face: &Color, row: usize, col: usize, color: &'static Color) -> Result<(), FaceletError> {
+        self.set_stickers(&[(face, row, col, color)])
+    }
+
+    /// Paints each sticker, given as the face, row, column and color set_sticker takes, and then
+    /// checks the result as from_facelets does. Fails, leaving self unchanged, if the stickers do
+    /// not form a real cube. On success self equals the same state reached by turning.
+    ///
+    /// Panics if any row or col is not less than SIDE_LEN.
+    pub fn set_stickers(&mut self, stickers: &[(&Color, usize, usize, &'static Color)]) -> Result<(), FaceletError> {
+        let mut faces = ALL_COLORS.map(|face| self.face_colors(face).unwrap());
+        for (face, row, col, color) in stickers {
+            assert!(*row < SIDE_LEN && *col < SIDE_LEN, "({}, {}) is not on a face", row, col);
+            faces[face.idx][*row][*col] = color;
+        }
+
+        let cube = Self::from_facelets(faces)?;
+        self.blocks = cube.blocks;
+        self.unsolved_blocks = cube.unsolved_blocks;
+        Ok(())
+    }
+
+    /// Like set_sticker, but makes no check that the cube is still real afterwards, for building
+    /// states by hand one sticker at a time: flipping an edge in place, for one, has to pass
+    /// through an edge with two stickers of the same color. Methods other than face_colors may give
+    /// meaningless results until the stickers form a real cube again, at which point it equals the
+    /// same state reached by turning.
+    ///
+    /// Panics if row or col is not less than SIDE_LEN, or if the sticker is a center and color is
+    /// not already its color, since centers never change.
+    pub fn set_sticker_unchecked(&mut self, face: &Color, row: usize, col: usize, color: &'static Color) {
+        assert!(row < SIDE_LEN && col < SIDE_LEN, "({}, {}) is not on a face", row, col);
+        if (row, col) == (1, 1) {
+            assert!(color == face, "The center of {} can't be painted {}", face.name, color.name);
+            return;
+        }
+
+        let neighbors = face.adjacent();
+        let others = match EDGE_CELLS.iter().position(|cell| *cell == (row, col)) {
+            Some(i) => vec![neighbors[i]],
+            None => {
+                let i = CORNER_CELLS.iter().position(|cell| *cell == (row, col)).unwrap();
+                vec![neighbors[i], neighbors[(i + 1) % NUM_NEIGHBORS]]
+            },
+        };
+        let position = color_set_key(others.into_iter().chain([face]));
+        let idx = self.blocks.iter()
+            .position(|block| {
+                color_set_key(block.faces().into_iter()) == position
+            })
+            .expect("Every sticker other than a center belongs to a block");
+
+        self.blocks[idx].stickers_mut().into_iter()
+            .filter(|block_face| block_face.face == face)
+            .for_each(|block_face| block_face.color = color);
+
+        // Stores the pieces where from_facelets would, so that equality with turned cubes holds.
+        let faces = ALL_COLORS.map(|face| self.face_colors(face).unwrap());
+        if let Ok(cube) = Self::from_facelets(faces) {
+            self.blocks = cube.blocks;
+        }
+        self.count_unsolved_blocks();
+    }
+
+    /// Returns the superflip: every edge flipped in place with everything else solved. This is one
+    /// of the positions furthest from solved.
+    pub fn superflip() -> Self {
+        let mut result = Self::solved();
+        for block in result.blocks.iter_mut() {
+            if let Block::Edge(a, b) = block {
+                std::mem::swap(&mut a.face, &mut b.face);
+            }
+        }
+        result.count_unsolved_blocks();
+        result
+    }
+
+    /// Returns the state of self after rotating the whole cube by symmetry and then repainting
+    /// every sticker so each face's center keeps its color. The result is exactly as far from
+    /// solved as self. symmetry may also be a reflection.
+    fn conjugate(&self, symmetry: &Symmetry) -> Self {
+        // Blocks are always stored in the slot and sticker order that solved() builds them in.
+        let mut home_slots = [0; 1 << NUM_COLORS];
+        for (slot, block) in self.blocks.iter().enumerate() {
+            home_slots[color_set_key(block.colors().into_iter())] = slot;
+        }
+
+        let mut blocks = self.blocks.clone();
+        for block in self.blocks.iter() {
+            let map = |block_face: &BlockFace<'a>| BlockFace {
+                color: symmetry[block_face.color.idx],
+                face: symmetry[block_face.face.idx],
+            };
+            let mapped: Vec<BlockFace<'a>> = block.stickers().into_iter().map(map).collect();
+
+            let slot = home_slots[color_set_key(mapped.iter().map(|block_face| block_face.color))];
+            let find = |color: &Color| mapped.iter()
+                .find(|block_face| block_face.color == color)
+                .expect("Symmetries map blocks onto blocks")
+                .clone();
+            blocks[slot] = match &self.blocks[slot] {
+                Block::Edge(a, b) => Block::Edge(find(a.color), find(b.color)),
+                Block::Corner(a, b, c) => Block::Corner(find(a.color), find(b.color), find(c.color)),
+            };
+        }
+
+        let mut result = Self { blocks, unsolved_blocks: 0, history: None, scheme: self.scheme };
+        result.count_unsolved_blocks();
+        result
+    }
+
+    /// Returns the mirror image of self in a mirror beside the blue face, which swaps blue and green
+    /// on both faces and stickers. Every sequence of rotations applied to self has the same effect
+    /// on the result once each rotation is replaced by its Rotation::mirror, so mirroring a solution
+    /// of the result gives a solution of self.
+    pub fn mirror(&self) -> Self {
+        let mut reflection = ALL_COLORS;
+        reflection.swap(BLUE.idx, GREEN.idx);
+        self.conjugate(&reflection)
+    }
+
+    /// Returns the face index of every sticker in block order, which identifies a state.
+    fn state_key(&self) -> Vec<usize> {
+        self.blocks.iter()
+            .flat_map(Block::stickers)
+            .map(|block_face| block_face.face.idx)
+            .collect()
+    }
+
+    /// Returns the state among self and its 23 rotated and repainted equivalents that comes first
+    /// when the faces of their stickers are compared in block order. Cubes that are equivalent under
+    /// whole-cube rotation have the same canonical form.
+    pub fn canonical_form(&self) -> Self {
+        symmetries().iter()
+            .map(|symmetry| self.conjugate(symmetry))
+            .min_by_key(|cube| cube.state_key())
+            .expect("There is at least the identity symmetry")
+    }
+
+    /// Returns a hash of canonical_form, so cubes that are equivalent under whole-cube rotation
+    /// hash the same. The value is only stable within one build of the program.
+    pub fn symmetry_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.canonical_form().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// A breadth first search with a visited set that treats states equivalent under whole-cube
+    /// rotation as already visited, which shrinks each depth of the search by up to a factor of
+    /// 24. The solution applies to self, not its canonical form.
+    pub fn solve_symmetry_reduced(&self) -> Vec<Rotation> {
+        let start = self.without_history();
+        if start.is_solved() {
+            return Vec::new();
+        }
+
+        let all_rotations = Rotation::all();
+
+        let mut visited = HashSet::new();
+        visited.insert(start.canonical_form());
+        let mut frontier = vec![(start, Vec::new())];
+
+        while !frontier.is_empty() {
+            let mut next_frontier = Vec::new();
+            for (cube, path) in frontier.iter() {
+                for rotation in all_rotations.iter() {
+                    let mut next = cube.clone();
+                    next.apply(rotation);
+                    let mut next_path: Vec<Rotation> = path.clone();
+                    next_path.push(rotation.clone());
+
+                    if next.is_solved() {
+                        return next_path;
+                    }
+                    if visited.insert(next.canonical_form()) {
+                        next_frontier.push((next, next_path));
+                    }
+                }
+            }
+            frontier = next_frontier;
+        }
+
+        Vec::new()
+    }
+
+    /// Returns a copy of self in the same state but without any history, so that searches can
+    /// clone states without also cloning the moves that led to them.
+    fn without_history(&self) -> Self {
+        Self { blocks: self.blocks.clone(), unsolved_blocks: self.unsolved_blocks, history: None, scheme: self.scheme }
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.unsolved_blocks == 0
+    }
+
+    /// Sets unsolved_blocks from blocks, for the few places that change blocks other than by
+    /// turning.
+    fn count_unsolved_blocks(&mut self) {
+        self.unsolved_blocks = self.blocks.iter().filter(|block| !block.is_solved()).count() as u8;
+    }
+
+    /// Returns the indices of the blocks that are in their home position and oriented. A block keeps
+    /// the same index for the lifetime of the cube, as in turn_tracked.
+    pub fn solved_pieces(&self) -> Vec<usize> {
+        self.blocks.iter()
+            .enumerate()
+            .filter(|(_, block)| block.is_solved())
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Returns how many layers are solved counting up from the bottom, where white is the bottom as
+    /// in solve_layered. The middle layer only counts once the bottom is solved and the top only
+    /// once both are, so this is 0 to 3 like the stages of a beginner's solve.
+    pub fn solved_layers(&self) -> usize {
+        [Some(&WHITE), None, Some(&YELLOW)].into_iter()
+            .take_while(|layer| self.blocks.iter()
+                .filter(|block| layer_of(block) == *layer)
+                .all(|block| block.is_solved()))
+            .count()
+    }
+
+    /// Returns how many stickers are not on their home face. Centers never move, so this is at
+    /// most 48. A single quarter turn leaves 12 misplaced: one on each of the 4 edges it moves and
+    /// two on each of the 4 corners.
+    pub fn misplaced_count(&self) -> usize {
+        self.blocks.iter()
+            .flat_map(Block::stickers)
+            .filter(|block_face| !block_face.is_solved())
+            .count()
+    }
+
+    /// Returns a shortest solution in quarter turns by breadth first search. Moves are expanded in
+    /// the order of Rotation::all(), so the same cube always gets the same solution: the first of
+    /// the shortest ones in that order.
+    pub fn solve(&self) -> Vec<Rotation> {
+        self.solve_reported()
+            .map(|report| report.solution)
+            .unwrap_or_default()
+    }
+
+    /// Runs the same search as solve and reports how much work it took. Returns None if the
+    /// search runs out of states without solving the cube.
+    pub fn solve_reported(&self) -> Option<SolveReport> {
+        let start = Instant::now();
+        let copy = self.without_history();
+
+        let mut queue = VecDeque::new();
+        if copy.is_solved() {
+            return Some(SolveReport {
+                solution: Vec::new(),
+                nodes_expanded: 0,
+                elapsed: start.elapsed(),
+                max_depth: 0,
+            });
+        }
+        queue.push_back((copy, Vec::new()));
+
+        let all_rotations = Rotation::all();
+
+        let mut i = 0;
+        while let Some((next, next_acc)) = queue.pop_front() {
+            i += 1;
+
+            if next.is_solved() {
+                return Some(SolveReport {
+                    max_depth: next_acc.len(),
+                    solution: next_acc,
+                    nodes_expanded: i,
+                    elapsed: start.elapsed(),
+                });
+            }
+
+            for rotation in all_rotations.iter() {
+                let mut next_copy = next.clone();
+                let mut next_acc_copy = next_acc.clone();
+
+                next_copy.apply(rotation);
+                next_acc_copy.push(rotation.clone());
+                queue.push_back((next_copy, next_acc_copy));
+            }
+        }
+
+        None
+    }
+
+    /// Runs the same search as solve, but gives up once budget has passed and returns the closest
+    /// it came instead. The clock is checked every TIMEOUT_CHECK_INTERVAL states, so it may run
+    /// slightly over.
+    pub fn solve_with_timeout(&self, budget: Duration) -> SolveOutcome {
+        let start = Instant::now();
+        let copy = self.without_history();
+        if copy.is_solved() {
+            return SolveOutcome::Solved(Vec::new());
+        }
+
+        let mut best = (copy.misplaced_count(), Vec::new());
+        let mut queue = VecDeque::new();
+        queue.push_back((copy, Vec::new()));
+
+        let all_rotations = Rotation::all();
+
+        let mut i = 0;
+        while let Some((next, next_acc)) = queue.pop_front() {
+            i += 1;
+            if i % TIMEOUT_CHECK_INTERVAL == 0 && start.elapsed() > budget {
+                let (misplaced, path) = best;
+                return SolveOutcome::BestEffort { path, misplaced };
+            }
+
+            if next.is_solved() {
+                return SolveOutcome::Solved(next_acc);
+            }
+
+            let misplaced = next.misplaced_count();
+            if misplaced < best.0 {
+                best = (misplaced, next_acc.clone());
+            }
+
+            for rotation in all_rotations.iter() {
+                let mut next_copy = next.clone();
+                let mut next_acc_copy = next_acc.clone();
+
+                next_copy.apply(rotation);
+                next_acc_copy.push(rotation.clone());
+                queue.push_back((next_copy, next_acc_copy));
+            }
+        }
+
+        unreachable!("Every expanded state queues its neighbors, so the queue never empties")
+    }
+
+    /// Returns every shortest solution in quarter turns, in the order of Rotation::all(). Searches
+    /// one depth at a time and keeps every path, so it grows by a factor of 12 per depth like
+    /// solve, and stops at the first depth where any path solves the cube.
+    pub fn solve_all_optimal(&self) -> Vec<Vec<Rotation>> {
+        let start = self.without_history();
+        if start.is_solved() {
+            return vec![Vec::new()];
+        }
+
+        let all_rotations = Rotation::all();
+        let mut frontier = vec![(start, Vec::new())];
+
+        loop {
+            let mut next_frontier = Vec::new();
+            for (cube, path) in frontier {
+                for rotation in all_rotations.iter() {
+                    let mut next = cube.clone();
+                    next.apply(rotation);
+                    let mut next_path: Vec<Rotation> = path.clone();
+                    next_path.push(rotation.clone());
+                    next_frontier.push((next, next_path));
+                }
+            }
+
+            let solutions: Vec<Vec<Rotation>> = next_frontier.iter()
+                .filter(|(cube, _)| cube.is_solved())
+                .map(|(_, path)| path.clone())
+                .collect();
+            if !solutions.is_empty() {
+                return solutions;
+            }
+            frontier = next_frontier;
+        }
+    }
+
+    /// Like solve, but only turns with the rotations in allowed and never expands the same state
+    /// twice. Returns None if no sequence of at most MAX_RESTRICTED_DEPTH of them solves the cube,
+    /// which also covers every state the allowed rotations can't solve at all.
+    pub fn solve_restricted(&self, allowed: &[Rotation]) -> Option<Vec<Rotation>> {
+        let start = self.without_history();
+        if start.is_solved() {
+            return Some(Vec::new());
+        }
+
+        let mut visited = HashSet::new();
+        visited.insert(start.clone());
+        let mut queue = VecDeque::from([(start, Vec::new())]);
+
+        while let Some((cube, path)) = queue.pop_front() {
+            if path.len() == MAX_RESTRICTED_DEPTH {
+                continue;
+            }
+
+            for rotation in allowed {
+                let mut next = cube.clone();
+                next.apply(rotation);
+                let mut next_path: Vec<Rotation> = path.clone();
+                next_path.push(rotation.clone());
+
+                if next.is_solved() {
+                    return Some(next_path);
+                }
+                if visited.insert(next.clone()) {
+                    queue.push_back((next, next_path));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Runs the same search as solve, yielding progress after each depth has been searched. The
+    /// final item carries the solution.
+    pub fn solve_iter(&self) -> impl Iterator<Item = SolveProgress> + 'a {
+        let copy = self.without_history();
+
+        let mut queue = VecDeque::new();
+        if !copy.is_solved() {
+            queue.push_back((copy, Vec::new()));
+        }
+
+        SolveIter { queue, all_rotations: Rotation::all(), depth: 0, nodes_expanded: 0, done: false }
+    }
+
+    /// Like solve, but expands each depth of the search across all available threads and never
+    /// expands the same state twice. Returns a solution of the same length solve would.
+    ///
+    /// The threads are std scoped threads rather than a rayon pool, so the crate keeps rand as its
+    /// only dependency. There is no visited set shared between threads: each thread only turns its
+    /// part of the frontier, and the children are checked against the visited set on this thread
+    /// once the whole depth is expanded.
+    pub fn solve_parallel(&self) -> Vec<Rotation> {
+        let start = self.without_history();
+        if start.is_solved() {
+            return Vec::new();
+        }
+
+        let all_rotations = Rotation::all();
+        let num_threads = thread::available_parallelism().map_or(1, |n| n.get());
+
+        let mut visited = HashSet::new();
+        visited.insert(start.clone());
+        let mut frontier = vec![(start, Vec::new())];
+
+        while !frontier.is_empty() {
+            let chunk_size = frontier.len().div_ceil(num_threads);
+            let children: Vec<(RubiksCube, Vec<Rotation>)> = thread::scope(|scope| {
+                let handles: Vec<_> = frontier.chunks(chunk_size)
+                    .map(|chunk| {
+                        let all_rotations = &all_rotations;
+                        scope.spawn(move || {
+                            let mut result = Vec::new();
+                            for (cube, path) in chunk {
+                                for rotation in all_rotations.iter() {
+                                    let mut next = cube.clone();
+                                    next.apply(rotation);
+                                    let mut next_path: Vec<Rotation> = path.clone();
+                                    next_path.push(rotation.clone());
+                                    result.push((next, next_path));
+                                }
+                            }
+                            result
+                        })
+                    })
+                    .collect();
+
+                handles.into_iter()
+                    .flat_map(|handle| handle.join().expect("Solver thread panicked"))
+                    .collect()
+            });
+
+            let mut next_frontier = Vec::new();
+            for (cube, path) in children {
+                if cube.is_solved() {
+                    return path;
+                }
+                if visited.insert(cube.clone()) {
+                    next_frontier.push((cube, path));
+                }
+            }
+            frontier = next_frontier;
+        }
+
+        Vec::new()
+    }
+
+    /// Searches outward from both self and the solved cube one depth at a time, always growing the
+    /// smaller frontier, until the two searches reach a common state. The returned rotations are
+    /// the path to that state followed by the inverse of the path from solved to it.
+    /// Returns None only if both searches run out of states to expand.
+    pub fn solve_bidirectional(&self) -> Option<Vec<Rotation>> {
+        self.solve_to(&RubiksCube::solved())
+    }
+
+    /// Like solve_bidirectional, but returns a shortest sequence of quarter turns that takes self
+    /// to goal rather than to the solved cube. Returns None if no sequence does, which is only
+    /// discovered after both searches have run out of states.
+    pub fn solve_to(&self, goal: &RubiksCube<'a>) -> Option<Vec<Rotation>> {
+        let start = self.without_history();
+        let goal = goal.without_history();
+        if start == goal {
+            return Some(Vec::new());
+        }
+
+        let all_rotations = Rotation::all();
+
+        let mut forward_visited = HashMap::new();
+        let mut backward_visited = HashMap::new();
+        forward_visited.insert(start.clone(), Vec::new());
+        backward_visited.insert(goal.clone(), Vec::new());
+        let mut forward_frontier = vec![start];
+        let mut backward_frontier = vec![goal];
+
+        while !forward_frontier.is_empty() || !backward_frontier.is_empty() {
+            let is_forward = backward_frontier.is_empty()
+                || (!forward_frontier.is_empty() && forward_frontier.len() <= backward_frontier.len());
+            let (frontier, visited, other_visited) = if is_forward {
+                (&mut forward_frontier, &mut forward_visited, &backward_visited)
+            } else {
+                (&mut backward_frontier, &mut backward_visited, &forward_visited)
+            };
+
+            let mut next_frontier = Vec::new();
+            for cube in frontier.iter() {
+                let path: Vec<Rotation> = visited[cube].clone();
+
+                for rotation in all_rotations.iter() {
+                    let mut next = cube.clone();
+                    next.apply(rotation);
+                    if visited.contains_key(&next) {
+                        continue;
+                    }
+
+                    let mut next_path = path.clone();
+                    next_path.push(rotation.clone());
+
+                    if let Some(other_path) = other_visited.get(&next) {
+                        let (forward_path, backward_path) = if is_forward {
+                            (&next_path, other_path)
+                        } else {
+                            (other_path, &next_path)
+                        };
+
+                        let mut result = forward_path.clone();
+                        result.extend(backward_path.iter().rev().map(Rotation::inverse));
+                        return Some(result);
+                    }
+
+                    visited.insert(next.clone(), next_path);
+                    next_frontier.push(next);
+                }
+            }
+
+            *frontier = next_frontier;
+        }
+
+        None
+    }
+
+    /// Uses the provided Rng to generate random Rotations and executes them on self.
+    /// Returns a Vec of the rotations that were executed in order.
+    pub fn scramble(&mut self, rng: &mut impl Rng, n: usize) -> Vec<Rotation> {
+        self.scramble_with_callback(rng, n, |_, _| ())
+    }
+
+    /// Like scramble, but calls f after each turn with the cube as it is now and the rotation
+    /// that was just applied.
+    pub fn scramble_with_callback(
+        &mut self,
+        rng: &mut impl Rng,
+        n: usize,
+        mut f: impl FnMut(&RubiksCube, &Rotation)
+    ) -> Vec<Rotation> {
+        let mut result = Vec::new();
+        for _ in 0..n {
+            let rotation = Rotation::random(rng);
+            self.turn(&rotation);
+            f(self, &rotation);
+            result.push(rotation);
+        }
+
+        result
+    }
+
+    /// Returns whether applying scramble and then solution to a solved cube leaves it solved.
+    pub fn verify_solution(scramble: &[Rotation], solution: &[Rotation]) -> bool {
+        let mut cube = RubiksCube::solved();
+        scramble.iter().for_each(|rotation| cube.apply(rotation));
+        verify(&cube, solution)
+    }
+
+    /// Like scramble but draws the rotations from a generator seeded with seed, so the same seed
+    /// and n always produce the same rotations.
+    pub fn scramble_seeded(&mut self, seed: u64, n: usize) -> Vec<Rotation> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        self.scramble(&mut rng, n)
+    }
+
+    /// Executes the specified rotation and, if history is enabled, records it. Any previously
+    /// undone rotations can no longer be redone.
+    pub fn turn(&mut self, rotation: &Rotation) {
+        self.apply(rotation);
+        if let Some(history) = &mut self.history {
+            history.done.push(rotation.clone());
+            history.undone.clear();
+        }
+    }
+
+    /// Turns face together with the middle layer next to it, in direction as seen looking at face.
+    /// That also turns the centers of the four sides, which this model keeps fixed, so it is applied
+    /// as the turn of the opposite face in the same direction, which is what the history records.
+    /// The two differ by a whole-cube turn, which is returned: for each position, indexed the same
+    /// as ALL_COLORS, the face that is there now. Rotations name faces by the color of their
+    /// center, so they still apply as given, but to turn the face at a position after a wide turn,
+    /// turn the returned face at that position's index instead. parse_sequence_with does this for
+    /// notation that names faces by position.
+    pub fn turn_wide(&mut self, face: &'static Color, direction: Direction) -> [&'static Color; NUM_COLORS] {
+        self.turn(&Rotation { face: face.opposite(), direction: direction.clone() });
+        whole_cube_turn(&Rotation { face, direction })
+    }
+
+    /// Turns only the middle layer next to face, in direction as seen looking at face. Like
+    /// turn_wide, it is applied as turns of the outer layers, face in the other direction and its
+    /// opposite in the same one, and returns the whole-cube turn the two differ by.
+    pub fn turn_slice(&mut self, face: &'static Color, direction: Direction) -> [&'static Color; NUM_COLORS] {
+        let rotation = Rotation { face, direction };
+        self.turn(&rotation.inverse());
+        self.turn(&Rotation { face: face.opposite(), direction: rotation.direction.clone() });
+        whole_cube_turn(&rotation)
     }
 
-    pub fn is_solved(&self) -> bool {
-        self.blocks.iter().all(|block| block.is_solved())
+    /// Like turn, but returns the indices of the blocks that were moved. A block keeps the same
+    /// index for the lifetime of the cube, no matter where it is moved to.
+    pub fn turn_tracked(&mut self, rotation: &Rotation) -> Vec<usize> {
+        let result = self.blocks.iter()
+            .enumerate()
+            .filter(|(_, block)| block.get_face(rotation.face).is_some())
+            .map(|(idx, _)| idx)
+            .collect();
+        self.turn(rotation);
+        result
     }
 
-    pub fn solve(&self) -> Vec<Rotation> {
-        let copy = self.clone();
+    /// Starts recording the rotations executed through turn so they can be undone. Cubes don't
+    /// record history by default. Does nothing if history is already enabled.
+    pub fn enable_history(&mut self) {
+        self.history.get_or_insert_with(History::default);
+    }
 
-        let mut queue = Queue::new();
+    /// Stops recording rotations and forgets any already recorded.
+    pub fn disable_history(&mut self) {
+        self.history = None;
+    }
 
-        if !copy.is_solved() {
-            queue.add((copy, Vec::new())).expect("");
+    /// Reverts the most recent rotation in the history. Does nothing if the history is empty or
+    /// not enabled.
+    pub fn undo(&mut self) {
+        if let Some(rotation) = self.history.as_mut().and_then(|history| history.done.pop()) {
+            self.apply(&rotation.inverse());
+            self.history.as_mut().unwrap().undone.push(rotation);
         }
+    }
 
-        let mut all_rotations = Vec::new();
-        for color in ALL_COLORS.iter() {
-            all_rotations.push(Rotation { face: color, direction: Direction::Clockwise });
-            all_rotations.push(Rotation { face: color, direction: Direction::CounterClockwise });
+    /// Re-executes the most recently undone rotation. Does nothing if there is nothing to redo.
+    pub fn redo(&mut self) {
+        if let Some(rotation) = self.history.as_mut().and_then(|history| history.undone.pop()) {
+            self.apply(&rotation);
+            self.history.as_mut().unwrap().done.push(rotation);
         }
+    }
 
-        let mut i = 0;
-        while queue.size() != 0 {
-            i = i + 1;
-            let (next, next_acc) = queue.remove().expect("Underflow");
-            
-            if next.is_solved() {
-                println!("Solve iterated {} times", i);
-                return next_acc;
-            }
+    /// Returns the rotations executed through turn since history was enabled, oldest first. Empty
+    /// if history is not enabled.
+    pub fn history(&self) -> &[Rotation] {
+        self.history.as_ref().map_or(&[], |history| &history.done)
+    }
 
-            for rotation in all_rotations.iter() {
-                let mut next_copy = next.clone();
-                let mut next_acc_copy = next_acc.clone();
+    /// Returns the state of the cube after each successive rotation in moves, starting from self.
+    /// self itself is not included, so there is one state per rotation and the last is the state
+    /// the whole sequence leads to. self is not modified and the states have no history.
+    pub fn states_along<'b>(&self, moves: &'b [Rotation]) -> impl Iterator<Item = RubiksCube<'a>> + 'b
+    where
+        'a: 'b,
+    {
+        let mut cube = self.without_history();
+        moves.iter().map(move |rotation| {
+            cube.apply(rotation);
+            cube.clone()
+        })
+    }
 
-                next_copy.turn(rotation);
-                next_acc_copy.push(rotation.clone());
-                queue.add((next_copy, next_acc_copy)).expect("");
-            }
+    /// Forgets all recorded rotations, including any that could have been redone. History stays
+    /// enabled if it was.
+    pub fn clear_history(&mut self) {
+        if let Some(history) = &mut self.history {
+            *history = History::default();
         }
+    }
 
-        println!("Solve iterated {} times", i);
+    /// Executes the specified rotation without recording it.
+    pub(crate) fn apply(&mut self, rotation: &Rotation) {
+        let face = rotation.face;
+        let rotations = get_color_rotations(rotation);
+        let mut unsolved = self.unsolved_blocks;
 
-        Vec::new()
+        self.blocks.iter_mut()
+            .filter(|block| block.get_face(face).is_some())
+            .for_each(|block| {
+                unsolved += block.is_solved() as u8;
+                match block {
+                    Block::Edge(ref mut a, ref mut b) => {
+                        a.face = rotations[a.face.idx].unwrap();
+                        b.face = rotations[b.face.idx].unwrap();
+                    },
+                    Block::Corner(ref mut a, ref mut b, ref mut c) => {
+                        a.face = rotations[a.face.idx].unwrap();
+                        b.face = rotations[b.face.idx].unwrap();
+                        c.face = rotations[c.face.idx].unwrap();
+                    }
+                }
+                unsolved -= block.is_solved() as u8;
+            });
+
+        self.unsolved_blocks = unsolved;
     }
 
-    /// Uses the provided Rng to generate random Rotations and executes them on self.
-    /// Returns a Vec of the rotations that were executed in order.
-    pub fn scramble(&mut self, rng: &mut impl Rng, n: usize) -> Vec<Rotation> {
-        let mut result = Vec::new();
-        for _ in 0..n {
-            let rotation = Rotation::random(rng);
-            self.turn(&rotation);
-            result.push(rotation);
+    /// Returns the orientation of the corner in each corner position, in the order the corners are
+    /// built by solved(). Each corner position is named by its white or yellow face followed by the
+    /// two side faces in clockwise order, and the orientation is the index into that name of the
+    /// face holding the corner's white or yellow sticker. A solved cube is all zeros and the sum
+    /// is always a multiple of 3. Quarter turns of white or yellow never change orientations.
+    pub fn corner_orientations(&self) -> [u8; 8] {
+        let mut result = [0; 8];
+
+        for (idx, position) in corner_positions().iter().enumerate() {
+            if let Some(Block::Corner(a, b, c)) = self.find_corner(position) {
+                let face = [a, b, c].into_iter()
+                    .find(|block_face| block_face.color == &WHITE || block_face.color == &YELLOW)
+                    .map(|block_face| block_face.face);
+                result[idx] = position.iter()
+                    .position(|color| Some(*color) == face)
+                    .unwrap_or(0) as u8;
+            }
         }
 
         result
     }
 
-    /// Executes the specified rotation
-    pub fn turn(&mut self, rotation: &Rotation) {
-        let face = rotation.face;
-        let rotations = get_color_rotations(&rotation);
+    /// Returns the orientation of the edge in each edge position, in the order the edges are built
+    /// by solved(). An edge's reference sticker is its white or yellow one if it has one and its red
+    /// or orange one otherwise. Likewise a position's reference face is its white or yellow face if
+    /// it has one and its red or orange face otherwise. The orientation is 0 when the reference
+    /// sticker is on the reference face and 1 when it isn't. A solved cube is all zeros, quarter
+    /// turns of red or orange flip four edges and no other turn changes orientations.
+    pub fn edge_orientations(&self) -> [u8; 12] {
+        let mut result = [0; 12];
 
-        self.blocks.iter_mut()
-            .filter(|block| block.get_face(face) != None)
-            .for_each(|block| match block {
-                Block::Edge(ref mut a, ref mut b) => {
-                    a.face = rotations[a.face.idx].unwrap();
-                    b.face = rotations[b.face.idx].unwrap();
-                },
-                Block::Corner(ref mut a, ref mut b, ref mut c) => {
-                    a.face = rotations[a.face.idx].unwrap();
-                    b.face = rotations[b.face.idx].unwrap();
-                    c.face = rotations[c.face.idx].unwrap();
+        for (idx, position) in edge_positions().iter().enumerate() {
+            if let Some(block @ Block::Edge(a, b)) = self.find_edge(position) {
+                let sticker = edge_reference([a.color, b.color]);
+                if block.face_of(sticker) != Some(edge_reference(*position)) {
+                    result[idx] = 1;
                 }
-            });
+            }
+        }
+
+        result
+    }
+
+    /// Returns, for each corner position in the order of corner_orientations, the index of the
+    /// position that the corner in it belongs in. A solved cube is [0, 1, ..., 7].
+    pub fn corner_permutation(&self) -> [u8; 8] {
+        let positions = corner_positions();
+        let positions: Vec<&[&Color]> = positions.iter().map(|position| &position[..]).collect();
+        self.permutation(&positions).try_into().expect("There are 8 corner positions")
+    }
+
+    /// Returns, for each edge position in the order of edge_orientations, the index of the position
+    /// that the edge in it belongs in. A solved cube is [0, 1, ..., 11].
+    pub fn edge_permutation(&self) -> [u8; 12] {
+        let positions = edge_positions();
+        let positions: Vec<&[&Color]> = positions.iter().map(|position| &position[..]).collect();
+        self.permutation(&positions).try_into().expect("There are 12 edge positions")
+    }
+
+    /// Returns, for each of the positions named by their faces, the index into positions of the
+    /// position that the block in it belongs in.
+    fn permutation(&self, positions: &[&[&Color]]) -> Vec<u8> {
+        let homes: Vec<usize> = positions.iter()
+            .map(|position| color_set_key(position.iter().copied()))
+            .collect();
+
+        positions.iter()
+            .map(|position| {
+                let block_faces = self.blocks.iter()
+                    .map(Block::stickers)
+                    .find(|block_faces| block_faces.len() == position.len()
+                        && block_faces.iter().all(|block_face| position.contains(&block_face.face)));
+                block_faces
+                    .and_then(|block_faces| {
+                        let key = color_set_key(block_faces.iter().map(|block_face| block_face.color));
+                        homes.iter().position(|home| *home == key)
+                    })
+                    .unwrap_or(0) as u8
+            })
+            .collect()
+    }
+
+    /// Finds the block whose stickers are the colors in colors, wherever it currently is.
+    pub(crate) fn find_block(&self, colors: &[&Color]) -> Option<&Block<'a>> {
+        self.blocks.iter().find(|block| {
+            let num_faces = match block {
+                Block::Edge(_, _) => 2,
+                Block::Corner(_, _, _) => 3,
+            };
+            num_faces == colors.len() && colors.iter().all(|color| block.face_of(color).is_some())
+        })
     }
 
     /// Finds the block that resides between the faces in colors.
-    fn find_edge(&self, colors: &[&Color; 2]) -> Option<&Block> {
+    pub(crate) fn find_edge(&self, colors: &[&Color; 2]) -> Option<&Block<'a>> {
         for block in self.blocks.iter() {
             match block {
                 Block::Edge(i, j) => {
@@ -182,7 +1389,7 @@ impl <'a> RubiksCube<'a> {
 
 
     /// Finds the block that resides between the faces in colors.
-    fn find_corner(&self, colors: &[&Color; 3]) -> Option<&Block> {
+    pub(crate) fn find_corner(&self, colors: &[&Color; 3]) -> Option<&Block<'a>> {
         for block in self.blocks.iter() {
             match block {
                 Block::Edge(_, _) => (),
@@ -199,67 +1406,114 @@ impl <'a> RubiksCube<'a> {
         None
     }
 
-    fn get_face(&self, face: &Color) -> Option<[[&str; SIDE_LEN]; SIDE_LEN]> {
-        let neighbors = ADJACENT_COLORS[face.idx];
+    /// Returns the colors on the specified face in row-major order as seen when looking directly at
+    /// that face with its first adjacent color on top. Returns None if the cube is malformed.
+    pub fn face_colors(&self, face: &'a Color) -> Option<[[&'a Color; SIDE_LEN]; SIDE_LEN]> {
+        let neighbors = face.adjacent();
         
-        let mut result = [["X"; SIDE_LEN]; SIDE_LEN];
+        let mut result = [[face; SIDE_LEN]; SIDE_LEN];
 
-        // Both edge and corner indexes go Top, Right, Bottom, Left
-        let edge_indexes = [(0, 1), (1, 2), (2, 1), (1, 0)];
-        let corner_indexes = [(0, 2), (2, 2), (2, 0), (0, 0)];
         for i in 0..NUM_NEIGHBORS {
             let edge = self.find_edge(&[face, neighbors[i]])?;
-            result[edge_indexes[i].0][edge_indexes[i].1] = edge.get_face(face)?.abrv;
+            result[EDGE_CELLS[i].0][EDGE_CELLS[i].1] = edge.get_face(face)?;
             let corner = self.find_corner(&[face, neighbors[i], neighbors[(i + 1) % NUM_NEIGHBORS]])?;
-            result[corner_indexes[i].0][corner_indexes[i].1] = corner.get_face(face)?.abrv;
+            result[CORNER_CELLS[i].0][CORNER_CELLS[i].1] = corner.get_face(face)?;
         }
-        result[1][1] = face.abrv;
 
         Some(result)
     }
+
+    /// Returns every sticker that differs between self and other along with its color on each.
+    /// Faces are in the order of ALL_COLORS and the stickers on each in row-major order.
+    pub fn diff(&self, other: &RubiksCube<'a>) -> Vec<StickerDiff<'a>> {
+        let mut result = Vec::new();
+        for face in ALL_COLORS {
+            let ours = self.face_colors(face).unwrap();
+            let theirs = other.face_colors(face).unwrap();
+            for row in 0..SIDE_LEN {
+                for col in 0..SIDE_LEN {
+                    if ours[row][col] != theirs[row][col] {
+                        result.push(StickerDiff { face, row, col, ours: ours[row][col], theirs: theirs[row][col] });
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Renders the cube the same way as Display, except that every sticker which is not on its
+    /// home face is shown in upper case.
+    pub fn to_diff_string(&self) -> String {
+        let mut faces = Vec::new();
+        for color in ALL_COLORS {
+            let face = self.face_colors(color).unwrap()
+                .map(|row| row.map(|sticker| {
+                    let shown = self.scheme.color_of(sticker).abrv;
+                    match sticker == color {
+                        true => shown.to_string(),
+                        false => shown.to_uppercase(),
+                    }
+                }));
+            faces.push(face);
+        }
+
+        Net(faces).to_string()
+    }
+
+    fn get_face(&self, face: &'a Color) -> Option<[[&str; SIDE_LEN]; SIDE_LEN]> {
+        Some(self.face_colors(face)?.map(|row| row.map(|color| self.scheme.color_of(color).abrv)))
+    }
 }
 
-fn write_face_row(
-    face: &[[&str; SIDE_LEN]; SIDE_LEN],
+/// Returns whether applying solution to initial leaves it solved. initial is not modified.
+pub fn verify(initial: &RubiksCube, solution: &[Rotation]) -> bool {
+    let mut cube = initial.without_history();
+    solution.iter().for_each(|rotation| cube.apply(rotation));
+    cube.is_solved()
+}
+
+fn write_face_row<T: Display>(
+    face: &[[T; SIDE_LEN]; SIDE_LEN],
     row: usize, f: &mut Formatter<'_>
 ) -> Result<(), Error> {
     write!(f, "| {} {} {} |", face[row][0], face[row][1], face[row][2])
 }
 
-fn write_multiple_face_rows(
-    faces: &Vec<[[&str; SIDE_LEN]; SIDE_LEN]>, 
+fn write_multiple_face_rows<T: Display>(
+    faces: &[[[T; SIDE_LEN]; SIDE_LEN]], 
     row: usize, f: &mut Formatter<'_>
 ) -> Result<(), Error> {
     for face in faces {
         write_face_row(face, row, f)?;
     }
-    writeln!(f, "")?;
+    writeln!(f)?;
     Ok(())
 }
 
-fn write_single_face(
-    face: &[[&str; SIDE_LEN]; SIDE_LEN], 
+fn write_single_face<T: Display>(
+    face: &[[T; SIDE_LEN]; SIDE_LEN], 
     left_pad: &str, 
     f: &mut Formatter<'_>
 ) -> Result<(), Error> {
     write!  (f, "{}", left_pad)?;
     write_face_row(face, 0, f)?;
-    writeln!(f, "")?;
+    writeln!(f)?;
     write!  (f, "{}", left_pad)?;
     write_face_row(face, 1, f)?;
-    writeln!(f, "")?;
+    writeln!(f)?;
     write!  (f, "{}", left_pad)?;
     write_face_row(face, 2, f)?;
-    writeln!(f, "")?;
+    writeln!(f)?;
     Ok(())
 }
 
-impl <'a> Display for RubiksCube<'a> {
+/// The stickers of all six faces in ALL_COLORS order, displayed as an unfolded cube.
+struct Net<T>(Vec<[[T; SIDE_LEN]; SIDE_LEN]>);
+
+impl <T: Display> Display for Net<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
-        let mut faces = Vec::new();
-        for color in ALL_COLORS {
-            faces.push(self.get_face(color).unwrap());
-        }
+        let faces = &self.0;
 
         let blank = "         ";
         let dash = "---------";
@@ -267,10 +1521,10 @@ impl <'a> Display for RubiksCube<'a> {
         writeln!(f, "{}{}", blank, dash)?;
         write_single_face(&faces[0], blank, f)?;
         writeln!(f, "{}{}{}{}", dash, dash, dash, dash)?;
-        let middle_faces = vec! [faces[1], faces[2], faces[3], faces[4]];
-        write_multiple_face_rows(&middle_faces, 0, f)?;
-        write_multiple_face_rows(&middle_faces, 1, f)?;
-        write_multiple_face_rows(&middle_faces, 2, f)?;
+        let middle_faces = &faces[1..5];
+        write_multiple_face_rows(middle_faces, 0, f)?;
+        write_multiple_face_rows(middle_faces, 1, f)?;
+        write_multiple_face_rows(middle_faces, 2, f)?;
         writeln!(f, "{}{}{}{}", dash, dash, dash, dash)?;
         write_single_face(&faces[5], blank, f)?;
         writeln!(f, "{}{}", blank, dash)?;
@@ -278,3 +1532,626 @@ impl <'a> Display for RubiksCube<'a> {
         Ok(())
     }
 }
+
+impl <'a> Display for RubiksCube<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let mut faces = Vec::new();
+        for color in ALL_COLORS {
+            let face = self.face_colors(color).unwrap()
+                .map(|row| row.map(|sticker| self.scheme.color_of(sticker).abrv));
+            faces.push(face);
+        }
+
+        Net(faces).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scrambled(seed: u64, n: usize) -> RubiksCube<'static> {
+        let mut cube = RubiksCube::solved();
+        cube.scramble_seeded(seed, n);
+        cube
+    }
+
+    #[test]
+    fn solve_bidirectional_solves_with_shortest_length() {
+        for seed in 0..5 {
+            let cube = scrambled(seed, 4);
+            let solution = cube.solve_bidirectional().unwrap();
+            assert!(verify(&cube, &solution));
+            assert_eq!(solution.len(), cube.solve().len());
+        }
+    }
+
+    #[test]
+    fn solve_bidirectional_of_solved_is_empty() {
+        assert_eq!(RubiksCube::solved().solve_bidirectional(), Some(Vec::new()));
+    }
+
+    #[test]
+    fn cubes_solved_after_different_scrambles_are_equal() {
+        let mut a = scrambled(1, 3);
+        let mut b = scrambled(2, 3);
+        assert!(a != b);
+
+        a.solve().iter().for_each(|rotation| a.turn(rotation));
+        b.solve().iter().for_each(|rotation| b.turn(rotation));
+        assert!(a == b);
+        assert!(a == RubiksCube::solved());
+    }
+
+    #[test]
+    fn face_colors_of_solved_are_the_face_color() {
+        let cube = RubiksCube::solved();
+        for face in ALL_COLORS {
+            let colors = cube.face_colors(face).unwrap();
+            assert!(colors.iter().flatten().all(|color| *color == face));
+        }
+    }
+
+    #[test]
+    fn undo_returns_to_prior_state_and_redo_reapplies() {
+        let mut cube = scrambled(3, 4);
+        cube.enable_history();
+        let before = cube.clone();
+        let rotation = Rotation { face: &RED, direction: Direction::Clockwise };
+        cube.turn(&rotation);
+        let after = cube.clone();
+
+        cube.undo();
+        assert!(cube == before);
+        assert!(cube.history().is_empty());
+        cube.redo();
+        assert!(cube == after);
+        assert_eq!(cube.history(), [rotation]);
+    }
+
+    #[test]
+    fn undo_without_history_does_nothing() {
+        let mut cube = scrambled(4, 4);
+        let before = cube.clone();
+        cube.undo();
+        assert!(cube == before);
+
+        cube.enable_history();
+        cube.undo();
+        cube.redo();
+        assert!(cube == before);
+    }
+
+    #[test]
+    fn history_is_only_recorded_once_enabled() {
+        let mut cube = scrambled(5, 4);
+        assert!(cube.history().is_empty());
+
+        cube.enable_history();
+        let rotations = cube.scramble_seeded(6, 3);
+        assert_eq!(cube.history(), rotations);
+
+        cube.disable_history();
+        assert!(cube.history().is_empty());
+    }
+
+    #[test]
+    fn scramble_seeded_is_reproducible() {
+        let mut a = RubiksCube::solved();
+        let mut b = RubiksCube::solved();
+        assert_eq!(a.scramble_seeded(7, 20), b.scramble_seeded(7, 20));
+        assert!(a == b);
+    }
+
+    #[test]
+    fn verify_accepts_the_inverse_of_a_known_scramble() {
+        let scramble = parse_sequence("w r b'").unwrap();
+        let inverse = parse_sequence("b r' w'").unwrap();
+        assert!(RubiksCube::verify_solution(&scramble, &inverse));
+        assert!(!RubiksCube::verify_solution(&scramble, &scramble));
+
+        let cube = RubiksCube::from_scramble("w r b'").unwrap();
+        assert!(verify(&cube, &inverse));
+        assert!(!verify(&cube, &inverse[1..]));
+    }
+
+    #[test]
+    fn solve_parallel_matches_solve_length() {
+        let cube = scrambled(8, 4);
+        let solution = cube.solve_parallel();
+        assert!(verify(&cube, &solution));
+        assert_eq!(solution.len(), cube.solve().len());
+    }
+
+    fn marked_stickers(cube: &RubiksCube) -> usize {
+        cube.to_diff_string().chars().filter(char::is_ascii_uppercase).count()
+    }
+
+    #[test]
+    fn to_diff_string_marks_misplaced_stickers() {
+        assert_eq!(marked_stickers(&RubiksCube::solved()), 0);
+
+        // A quarter turn moves one sticker of each of four edges and two of each of four corners
+        // off their home faces.
+        let cube = RubiksCube::from_scramble("r").unwrap();
+        assert_eq!(marked_stickers(&cube), 12);
+    }
+
+    #[test]
+    fn superflip_is_a_valid_state_with_corners_home() {
+        let cube = RubiksCube::superflip();
+        assert!(!cube.is_solved());
+        assert!(RubiksCube::from_facelet_string(&cube.to_facelet_string()).is_ok_and(|parsed| parsed == cube));
+
+        assert_eq!(cube.corner_permutation(), [0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(cube.corner_orientations(), [0; 8]);
+        assert_eq!(cube.edge_orientations(), [1; 12]);
+    }
+
+    #[test]
+    fn solve_iter_ends_with_the_solution_of_solve() {
+        let cube = scrambled(9, 3);
+        let progress: Vec<SolveProgress> = cube.solve_iter().collect();
+        let last = progress.last().unwrap();
+        assert_eq!(last.solution, Some(cube.solve()));
+        assert!(progress[..progress.len() - 1].iter().all(|item| item.solution.is_none()));
+    }
+
+    #[test]
+    fn solve_symmetry_reduced_solves_the_original_scramble() {
+        for seed in 0..3 {
+            let cube = scrambled(seed, 3);
+            // Otherwise the test couldn't tell a solution of self from one of the canonical form.
+            assert!(cube.canonical_form() != cube);
+
+            let solution = cube.solve_symmetry_reduced();
+            assert!(verify(&cube, &solution));
+            assert_eq!(solution.len(), cube.solve().len());
+        }
+    }
+
+    #[test]
+    fn turn_tracked_reports_the_eight_blocks_turned() {
+        let mut cube = scrambled(10, 5);
+        let moved = cube.turn_tracked(&Rotation { face: &BLUE, direction: Direction::Clockwise });
+        assert_eq!(moved.len(), 8);
+        assert!(moved.iter().all(|idx| cube.blocks[*idx].get_face(&BLUE).is_some()));
+    }
+
+    #[test]
+    fn solved_orientations_are_zero() {
+        let cube = RubiksCube::solved();
+        assert_eq!(cube.corner_orientations(), [0; 8]);
+        assert_eq!(cube.edge_orientations(), [0; 12]);
+    }
+
+    #[test]
+    fn single_turns_change_orientations_as_documented() {
+        let white = RubiksCube::from_scramble("w").unwrap();
+        assert_eq!(white.corner_orientations(), [0; 8]);
+        assert_eq!(white.edge_orientations(), [0; 12]);
+
+        let red = RubiksCube::from_scramble("r").unwrap();
+        assert_eq!(red.edge_orientations().iter().filter(|orientation| **orientation == 1).count(), 4);
+        assert_eq!(red.corner_orientations().iter().filter(|orientation| **orientation != 0).count(), 4);
+        assert_eq!(red.corner_orientations().iter().sum::<u8>() % 3, 0);
+
+        let blue = RubiksCube::from_scramble("b").unwrap();
+        assert_eq!(blue.edge_orientations(), [0; 12]);
+        assert_eq!(blue.corner_orientations().iter().filter(|orientation| **orientation != 0).count(), 4);
+    }
+
+    #[test]
+    fn solve_restricted_fails_when_the_moves_cannot_solve() {
+        let cube = RubiksCube::from_scramble("r").unwrap();
+        let white_only: Vec<Rotation> = Rotation::all().into_iter().filter(|rotation| rotation.face == &WHITE).collect();
+        assert_eq!(cube.solve_restricted(&white_only), None);
+    }
+
+    #[test]
+    fn solve_restricted_with_every_move_matches_solve() {
+        let cube = scrambled(11, 4);
+        let solution = cube.solve_restricted(&Rotation::all()).unwrap();
+        assert!(verify(&cube, &solution));
+        assert_eq!(solution.len(), cube.solve().len());
+    }
+
+    fn facelets(cube: &RubiksCube<'static>) -> [[[&'static Color; SIDE_LEN]; SIDE_LEN]; NUM_COLORS] {
+        ALL_COLORS.map(|face| cube.face_colors(face).unwrap())
+    }
+
+    #[test]
+    fn from_facelets_reconstructs_a_scrambled_cube() {
+        let cube = scrambled(12, 20);
+        assert!(RubiksCube::from_facelets(facelets(&cube)).is_ok_and(|rebuilt| rebuilt == cube));
+    }
+
+    #[test]
+    fn from_facelet_string_rejects_malformed_strings() {
+        assert_eq!(RubiksCube::from_facelet_string("wrb").err(), Some(FaceletError::WrongLength(3)));
+
+        let facelets = RubiksCube::solved().to_facelet_string().replacen('r', "x", 1);
+        assert_eq!(RubiksCube::from_facelet_string(&facelets).err(), Some(FaceletError::UnknownColor('x')));
+    }
+
+    #[test]
+    fn from_facelets_rejects_stickers_no_cube_has() {
+        let solved = RubiksCube::solved();
+        // The sticker of the white face's top edge, which borders green.
+        let set_white_green_edge = |color| {
+            let mut faces = facelets(&solved);
+            faces[WHITE.idx][0][1] = color;
+            RubiksCube::from_facelets(faces).err()
+        };
+        assert_eq!(set_white_green_edge(&GREEN), Some(FaceletError::UnknownPiece("gg".to_string())));
+        assert!(matches!(set_white_green_edge(&YELLOW), Some(FaceletError::DuplicatePiece(_))));
+
+        let mut faces = facelets(&solved);
+        faces[WHITE.idx][1][1] = &RED;
+        assert_eq!(RubiksCube::from_facelets(faces).err(), Some(FaceletError::WrongCenter("w".to_string())));
+    }
+
+    #[test]
+    fn from_facelets_rejects_states_turns_cannot_reach() {
+        let corners: [u8; 8] = std::array::from_fn(|i| i as u8);
+        let edges: [u8; 12] = std::array::from_fn(|i| i as u8);
+        let check = |corner_permutation, corner_orientations, edge_permutation, edge_orientations| {
+            let cube = RubiksCube::from_pieces(&corner_permutation, &corner_orientations, &edge_permutation, &edge_orientations);
+            RubiksCube::from_facelets(facelets(&cube)).err()
+        };
+
+        let mut twisted = [0; 8];
+        twisted[0] = 1;
+        assert_eq!(check(corners, twisted, edges, [0; 12]), Some(FaceletError::TwistedCorner));
+
+        let mut flipped = [0; 12];
+        flipped[0] = 1;
+        assert_eq!(check(corners, [0; 8], edges, flipped), Some(FaceletError::FlippedEdge));
+
+        let mut swapped = edges;
+        swapped.swap(0, 1);
+        assert_eq!(check(corners, [0; 8], swapped, [0; 12]), Some(FaceletError::ParityMismatch));
+    }
+
+    #[test]
+    fn misplaced_count_counts_moved_stickers() {
+        assert_eq!(RubiksCube::solved().misplaced_count(), 0);
+        // The turned face keeps its own stickers; the 3 on each of the 4 sides move.
+        assert_eq!(RubiksCube::from_scramble("w").unwrap().misplaced_count(), 12);
+    }
+
+    #[test]
+    fn solve_is_deterministic() {
+        let cube = scrambled(564, 4);
+        assert_eq!(cube.solve(), cube.solve());
+    }
+
+    #[test]
+    fn solve_finds_the_same_solutions_as_before() {
+        let cases = [("w y", "w' y'"), ("w r b'", "b r' w'"), ("w2 y", "w w y'")];
+        for (scramble, solution) in cases {
+            let cube = RubiksCube::from_scramble(scramble).unwrap();
+            assert_eq!(cube.solve(), parse_sequence(solution).unwrap());
+        }
+    }
+
+    #[test]
+    fn solve_with_timeout_gives_up_on_a_tiny_budget() {
+        let cube = RubiksCube::from_scramble("w r b o g y").unwrap();
+        assert!(matches!(cube.solve_with_timeout(Duration::ZERO), SolveOutcome::BestEffort { .. }));
+    }
+
+    #[test]
+    fn solve_with_timeout_solves_within_a_generous_budget() {
+        let cube = RubiksCube::from_scramble("w r").unwrap();
+        match cube.solve_with_timeout(Duration::from_secs(60)) {
+            SolveOutcome::Solved(solution) => assert!(verify(&cube, &solution)),
+            SolveOutcome::BestEffort { .. } => panic!("A 2 move scramble should be solved in time"),
+        }
+    }
+
+    #[test]
+    fn solve_all_optimal_finds_every_order_of_commuting_turns() {
+        let cube = RubiksCube::from_scramble("w y").unwrap();
+        let solutions = cube.solve_all_optimal();
+        assert_eq!(solutions, vec![parse_sequence("w' y'").unwrap(), parse_sequence("y' w'").unwrap()]);
+        assert!(solutions.iter().all(|solution| verify(&cube, solution)));
+    }
+
+    #[test]
+    fn is_oriented_agrees_with_edge_orientations() {
+        for seed in 0..20 {
+            let cube = scrambled(seed, 20);
+            let flipped = cube.blocks.iter()
+                .filter(|block| matches!(block, Block::Edge(..)) && !block.is_oriented(&WHITE))
+                .count();
+            assert_eq!(flipped, cube.edge_orientations().iter().filter(|orientation| **orientation == 1).count());
+        }
+    }
+
+    #[test]
+    fn symmetry_hash_is_the_same_for_rotated_scrambles() {
+        let hash = |notation| RubiksCube::from_scramble(notation).unwrap().symmetry_hash();
+        assert_eq!(hash("w r"), hash("w g"));
+        assert_eq!(hash("w r"), hash("y b"));
+        assert_ne!(hash("w r"), hash("w r2"));
+
+        let cube = scrambled(579, 10);
+        for symmetry in symmetries() {
+            assert_eq!(cube.conjugate(symmetry).symmetry_hash(), cube.symmetry_hash());
+        }
+    }
+
+    #[test]
+    fn scramble_with_callback_reports_every_turn() {
+        let mut cube = RubiksCube::solved();
+        let mut seen = Vec::new();
+        let rotations = cube.scramble_with_callback(&mut StdRng::seed_from_u64(580), 7, |state, rotation| {
+            seen.push((state.to_facelet_string(), rotation.clone()));
+        });
+
+        assert_eq!(seen.len(), 7);
+        assert_eq!(seen.iter().map(|(_, rotation)| rotation.clone()).collect::<Vec<_>>(), rotations);
+        assert!(seen.last().is_some_and(|(state, _)| *state == cube.to_facelet_string()));
+    }
+
+    #[test]
+    fn solved_pieces_lists_untouched_blocks() {
+        assert_eq!(RubiksCube::solved().solved_pieces(), (0..20).collect::<Vec<_>>());
+        assert_eq!(RubiksCube::from_scramble("w").unwrap().solved_pieces().len(), 12);
+    }
+
+    #[test]
+    fn diff_lists_changed_stickers_with_both_colors() {
+        assert!(scrambled(585, 10).diff(&scrambled(585, 10)).is_empty());
+
+        let turned = RubiksCube::from_scramble("w").unwrap();
+        let diff = turned.diff(&RubiksCube::solved());
+        assert_eq!(diff.len(), 12);
+        for sticker in diff {
+            assert!(sticker.face != &WHITE && sticker.face != &YELLOW);
+            assert_eq!(sticker.row, 0);
+            assert_eq!(sticker.theirs, sticker.face);
+            assert_ne!(sticker.ours, sticker.theirs);
+        }
+    }
+
+    #[test]
+    fn wide_turn_is_a_face_turn_plus_a_slice() {
+        for face in ALL_COLORS {
+            for direction in [Direction::Clockwise, Direction::CounterClockwise, Direction::Double] {
+                let mut wide = RubiksCube::solved();
+                let wide_frame = wide.turn_wide(face, direction.clone());
+
+                let mut split = RubiksCube::solved();
+                split.turn(&Rotation { face, direction: direction.clone() });
+                let split_frame = split.turn_slice(face, direction);
+
+                assert!(wide == split);
+                assert_eq!(wide_frame, split_frame);
+            }
+        }
+
+        let mut wide = RubiksCube::solved();
+        wide.turn_wide(&BLUE, Direction::Clockwise);
+        assert!(wide == RubiksCube::from_scramble("g").unwrap());
+
+        // With the centers kept fixed a double slice turn is the same as turning the faces on
+        // either side twice.
+        let mut slice = RubiksCube::solved();
+        slice.turn_slice(&BLUE, Direction::Double);
+        assert!(slice == RubiksCube::from_scramble("b2 g2").unwrap());
+        assert!(RubiksCube::from_scramble("bw b' bw b'").unwrap() == slice);
+    }
+
+    #[test]
+    fn turn_wide_returns_where_each_face_went() {
+        // Blue turning clockwise carries red to white's position, so the "w" in "bw w" turns red.
+        let mut cube = RubiksCube::solved();
+        let frame = cube.turn_wide(&BLUE, Direction::Clockwise);
+        assert_eq!(frame[WHITE.idx], &RED);
+
+        cube.turn(&Rotation { face: frame[WHITE.idx], direction: Direction::Clockwise });
+        assert!(cube == RubiksCube::from_scramble("bw w").unwrap());
+        assert!(cube == RubiksCube::from_scramble("g r").unwrap());
+    }
+
+    #[test]
+    fn solved_layers_counts_up_from_white() {
+        assert_eq!(RubiksCube::solved().solved_layers(), 3);
+        assert_eq!(RubiksCube::from_scramble("y").unwrap().solved_layers(), 2);
+        // Takes a middle edge out to the yellow layer, leaving only the white layer solved.
+        assert_eq!(RubiksCube::from_scramble("y g y' g' y' r' y r").unwrap().solved_layers(), 1);
+        assert_eq!(RubiksCube::from_scramble("w").unwrap().solved_layers(), 0);
+    }
+
+    #[test]
+    fn solve_to_reaches_the_goal() {
+        let start = scrambled(590, 3);
+        let goal = scrambled(591, 3);
+        let path = start.solve_to(&goal).unwrap();
+        assert!(path.len() <= 6);
+
+        let mut reached = start.clone();
+        path.iter().for_each(|rotation| reached.turn(rotation));
+        assert!(reached == goal);
+    }
+
+    #[test]
+    fn solve_reported_counts_expanded_nodes() {
+        let cube = scrambled(591, 3);
+        let report = cube.solve_reported().unwrap();
+        assert!(report.nodes_expanded > 0);
+        assert_eq!(report.max_depth, report.solution.len());
+        assert!(verify(&cube, &report.solution));
+    }
+
+    #[test]
+    fn solve_writes_nothing_to_stdout() {
+        // The test harness only captures output of the test's own thread, so check what this test
+        // writes when run again in a child process without capturing.
+        if std::env::var_os("RUBIKS_SOLVER_STDOUT_CHILD").is_some() {
+            print!("<");
+            scrambled(592, 3).solve();
+            print!(">");
+            return;
+        }
+
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "model::rubiks_cube::tests::solve_writes_nothing_to_stdout", "--nocapture"])
+            .env("RUBIKS_SOLVER_STDOUT_CHILD", "1")
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("<>"));
+    }
+
+    #[test]
+    fn default_is_solved() {
+        assert!(RubiksCube::default() == RubiksCube::solved());
+    }
+
+    #[test]
+    fn set_sticker_builds_a_turned_state() {
+        let target = scrambled(595, 15);
+        let mut stickers = Vec::new();
+        for face in ALL_COLORS {
+            for (row, colors) in target.face_colors(face).unwrap().into_iter().enumerate() {
+                for (col, color) in colors.into_iter().enumerate() {
+                    stickers.push((face, row, col, color));
+                }
+            }
+        }
+
+        let mut cube = RubiksCube::solved();
+        cube.set_stickers(&stickers).unwrap();
+        assert!(cube == target);
+
+        let mut unchecked = RubiksCube::solved();
+        for (face, row, col, color) in stickers {
+            unchecked.set_sticker_unchecked(face, row, col, color);
+        }
+        assert!(unchecked == target);
+    }
+
+    #[test]
+    fn set_sticker_rejects_cubes_turning_cant_reach() {
+        let mut cube = RubiksCube::from_scramble("w").unwrap();
+        assert_eq!(cube.set_sticker(&RED, 1, 1, &BLUE), Err(FaceletError::WrongCenter("r".to_string())));
+        assert!(cube.set_sticker(&RED, 0, 1, &YELLOW).is_err());
+        assert!(cube == RubiksCube::from_scramble("w").unwrap());
+
+        let color = cube.face_colors(&RED).unwrap()[0][1];
+        assert_eq!(cube.set_sticker(&RED, 0, 1, color), Ok(()));
+
+        // Swapping the two stickers of the white and red edge flips it.
+        let stickers = [(&WHITE, 1, 0, &RED), (&RED, 0, 1, &WHITE)];
+        let mut solved = RubiksCube::solved();
+        assert_eq!(solved.set_stickers(&stickers), Err(FaceletError::FlippedEdge));
+        assert!(solved.is_solved());
+    }
+
+    #[test]
+    fn every_view_of_a_cube_shows_its_scheme() {
+        use crate::model::color::JAPANESE;
+
+        assert!(JAPANESE.adjacent(&WHITE).contains(&&YELLOW));
+        assert_eq!(JAPANESE.opposite(&WHITE), &BLUE);
+
+        let solved = RubiksCube::solved_with_scheme(JAPANESE);
+        assert_eq!(&solved.to_facelet_string()[18..27], "yyyyyyyyy");
+
+        let mut cube = solved.clone();
+        cube.scramble_seeded(607, 3);
+        assert!(verify(&cube, &cube.solve()));
+        assert_eq!(cube.to_diff_string().to_lowercase(), cube.to_string());
+
+        let parsed = RubiksCube::from_facelet_string_with_scheme(&cube.to_facelet_string(), JAPANESE).unwrap();
+        assert!(parsed == cube);
+        assert_eq!(parsed.scheme(), JAPANESE);
+    }
+
+    #[test]
+    fn states_along_ends_at_the_whole_sequence() {
+        let start = scrambled(608, 5);
+        let moves = parse_sequence("w r' b2 o g y'").unwrap();
+
+        let states: Vec<RubiksCube> = start.states_along(&moves).collect();
+        assert_eq!(states.len(), moves.len());
+
+        let mut end = start.clone();
+        moves.iter().for_each(|rotation| end.turn(rotation));
+        assert!(states.last().is_some_and(|last| *last == end));
+    }
+
+    #[test]
+    fn mirrored_solutions_of_the_mirror_solve_the_original() {
+        for seed in 0..5 {
+            let cube = scrambled(612 + seed, 4);
+            let mirror = cube.mirror();
+            assert!(mirror.mirror() == cube);
+
+            let solution: Vec<Rotation> = mirror.solve().iter().map(Rotation::mirror).collect();
+            assert!(verify(&cube, &solution));
+        }
+    }
+
+    #[test]
+    fn is_solved_agrees_with_checking_every_block() {
+        let naive = |cube: &RubiksCube| cube.blocks.iter().all(|block| block.is_solved());
+
+        // Undoing the last turn half the time keeps the cube near solved, where the count changes
+        // the most.
+        let mut rng = StdRng::seed_from_u64(614);
+        let mut cube = RubiksCube::solved();
+        cube.enable_history();
+        for _ in 0..2000 {
+            match rng.gen_bool(0.5) {
+                true => cube.turn(&Rotation::random(&mut rng)),
+                false => { cube.undo(); },
+            }
+            assert_eq!(cube.is_solved(), naive(&cube));
+        }
+
+        let mut painted = RubiksCube::from_scramble("w").unwrap();
+        painted.set_sticker_unchecked(&RED, 0, 0, &RED);
+        assert_eq!(painted.is_solved(), naive(&painted));
+
+        let built = [
+            RubiksCube::superflip(),
+            scrambled(614, 20).mirror(),
+            scrambled(615, 20).canonical_form(),
+            RubiksCube::from_facelet_string(&scrambled(616, 20).to_facelet_string()).unwrap(),
+            crate::model::coord_cube::CoordCube::solved().to_cube(),
+        ];
+        for cube in built {
+            assert_eq!(cube.is_solved(), naive(&cube));
+        }
+    }
+
+    #[test]
+    fn solve_with_timeout_returns_the_closest_state_it_reached() {
+        let cube = RubiksCube::from_scramble("w r b o g y").unwrap();
+        match cube.solve_with_timeout(Duration::ZERO) {
+            SolveOutcome::BestEffort { path, misplaced } => {
+                assert!(misplaced < cube.misplaced_count());
+
+                let mut reached = cube.clone();
+                path.iter().for_each(|rotation| reached.turn(rotation));
+                assert_eq!(reached.misplaced_count(), misplaced);
+            },
+            SolveOutcome::Solved(_) => panic!("A 6 move scramble can't be solved without any time"),
+        }
+    }
+
+    #[test]
+    fn the_inverse_of_a_scramble_solves_it() {
+        let scramble = "w r' b2 o g' y w2";
+        let mut cube = RubiksCube::from_scramble(scramble).unwrap();
+        assert!(!cube.is_solved());
+
+        parse_sequence(scramble).unwrap().iter().rev().for_each(|rotation| cube.turn(&rotation.inverse()));
+        assert!(cube.is_solved());
+        assert_eq!(RubiksCube::from_scramble("w x").err(), Some(ParseError::UnknownFace("x".to_string())));
+    }
+}
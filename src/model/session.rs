@@ -0,0 +1,98 @@
+//! A plain text format for saving a scramble together with its solution, e.g.
+//!
+//! ```text
+//! rubiks-session v1
+//! scramble: w r' b2
+//! solution: b2 r w'
+//! ```
+//!
+//! The first line names the version of the format, so files written by a later version can be
+//! told apart instead of being misread.
+
+use std::fmt::{Display, Formatter, Error};
+
+use super::rotation::{format_sequence, parse_sequence, ParseError, Rotation};
+
+const HEADER: &str = "rubiks-session v";
+const VERSION: &str = "1";
+const SCRAMBLE_PREFIX: &str = "scramble:";
+const SOLUTION_PREFIX: &str = "solution:";
+
+/// The reasons a string could not be loaded by load_session.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum SessionError {
+    /// The first line is not a session header.
+    MissingHeader,
+    /// The header names this version, which this version of the crate can't read.
+    UnknownVersion(String),
+    /// There is no line starting with this prefix.
+    MissingLine(&'static str),
+    /// A line's rotations could not be parsed.
+    InvalidRotations(ParseError),
+}
+
+impl Display for SessionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            SessionError::MissingHeader => write!(f, "missing \"{}{}\" header", HEADER, VERSION),
+            SessionError::UnknownVersion(version) => write!(f, "unknown session version \"{}\"", version),
+            SessionError::MissingLine(prefix) => write!(f, "missing \"{}\" line", prefix),
+            SessionError::InvalidRotations(err) => write!(f, "invalid rotations: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+/// Writes scramble and solution in the current version of the format.
+pub fn save_session(scramble: &[Rotation], solution: &[Rotation]) -> String {
+    format!(
+        "{}{}\n{} {}\n{} {}\n",
+        HEADER, VERSION,
+        SCRAMBLE_PREFIX, format_sequence(scramble),
+        SOLUTION_PREFIX, format_sequence(solution)
+    )
+}
+
+/// Reads the scramble and solution written by save_session. Blank lines and whitespace around
+/// each line are ignored.
+pub fn load_session(s: &str) -> Result<(Vec<Rotation>, Vec<Rotation>), SessionError> {
+    let mut lines = s.lines().map(str::trim).filter(|line| !line.is_empty());
+
+    let version = lines.next()
+        .and_then(|line| line.strip_prefix(HEADER))
+        .ok_or(SessionError::MissingHeader)?;
+    if version != VERSION {
+        return Err(SessionError::UnknownVersion(version.to_string()));
+    }
+
+    let mut read = |prefix: &'static str| {
+        let rotations = lines.next()
+            .and_then(|line| line.strip_prefix(prefix))
+            .ok_or(SessionError::MissingLine(prefix))?;
+        parse_sequence(rotations).map_err(SessionError::InvalidRotations)
+    };
+    let scramble = read(SCRAMBLE_PREFIX)?;
+    let solution = read(SOLUTION_PREFIX)?;
+
+    Ok((scramble, solution))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sessions_round_trip() {
+        let scramble = parse_sequence("w r' b2 o").unwrap();
+        let solution = parse_sequence("o' b2 r w'").unwrap();
+        assert_eq!(load_session(&save_session(&scramble, &solution)), Ok((scramble, solution)));
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        let saved = save_session(&[], &[]).replacen(VERSION, "99", 1);
+        assert_eq!(load_session(&saved), Err(SessionError::UnknownVersion("99".to_string())));
+        assert_eq!(load_session("scramble: w"), Err(SessionError::MissingHeader));
+    }
+}
@@ -0,0 +1,315 @@
+//! Thistlethwaite's algorithm, which solves the cube by moving it through a chain of nested
+//! subgroups, each generated by a smaller set of moves than the last:
+//!
+//! * G0: any state, generated by every move.
+//! * G1: every edge oriented, generated by limiting red and orange to double turns.
+//! * G2: every corner oriented and the edges without white or yellow between those two faces,
+//!   generated by also limiting blue and green to double turns.
+//! * G3: every piece somewhere double turns can take it, generated by double turns alone.
+//! * G4: solved.
+//!
+//! The edge and corner orientations are those of RubiksCube::edge_orientations and
+//! RubiksCube::corner_orientations. Each phase only needs a few properties of the cube, so it
+//! tracks them as coordinates: one number per property value, found by a breadth first search from
+//! the solved cube using the phase's moves. A table of the distance from every combination of
+//! coordinates to the next subgroup then lets each phase follow a shortest path greedily.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::OnceLock;
+
+use super::color::{Color, ALL_COLORS, WHITE, YELLOW, RED, ORANGE, BLUE, GREEN};
+use super::rotation::{Direction, Rotation};
+use super::rubiks_cube::{edge_positions, RubiksCube};
+
+static PHASES: OnceLock<Vec<Phase>> = OnceLock::new();
+
+/// Reduces a cube to the property a coordinate tracks.
+type Projection = fn(&RubiksCube) -> Vec<u8>;
+
+/// A property of the cube along with how every move in a phase changes it.
+struct Coordinate {
+    project: Projection,
+    /// The number assigned to each value of the property.
+    ids: HashMap<Vec<u8>, usize>,
+    /// The id reached by applying move m to id i is at i * num_moves + m.
+    moves: Vec<u32>,
+}
+
+impl Coordinate {
+    /// Finds every value of the property reachable from the solved cube using moves, keeping one
+    /// cube for each to work out where each move takes it.
+    fn new(project: Projection, moves: &[Rotation]) -> Self {
+        let solved = RubiksCube::solved();
+        let mut ids = HashMap::from([(project(&solved), 0)]);
+        let mut representatives = vec![solved];
+        let mut table = Vec::new();
+
+        let mut i = 0;
+        while i < representatives.len() {
+            for rotation in moves {
+                let mut next = representatives[i].clone();
+                next.apply(rotation);
+
+                let value = project(&next);
+                let id = match ids.get(&value) {
+                    Some(id) => *id,
+                    None => {
+                        let id = representatives.len();
+                        ids.insert(value, id);
+                        representatives.push(next);
+                        id
+                    }
+                };
+                table.push(id as u32);
+            }
+            i += 1;
+        }
+
+        Coordinate { project, ids, moves: table }
+    }
+
+    fn size(&self) -> usize {
+        self.ids.len()
+    }
+
+    fn id(&self, cube: &RubiksCube) -> Option<usize> {
+        self.ids.get(&(self.project)(cube)).copied()
+    }
+}
+
+/// One step of the algorithm: the moves it may use and how far each state is from its goal.
+struct Phase {
+    moves: Vec<Rotation>,
+    coordinates: Vec<Coordinate>,
+    /// The distance to the goal of each combination of coordinate ids, indexed with the first
+    /// coordinate as the most significant digit.
+    distances: Vec<u8>,
+}
+
+impl Phase {
+    /// Builds the distance table by a breadth first search outward from the goal, which is the set
+    /// of coordinate combinations in goals. Every phase's move set contains the inverse of each of
+    /// its moves, so the distance from the goal is also the distance to it.
+    fn new(moves: Vec<Rotation>, projections: &[Projection], goals: Vec<Vec<usize>>) -> Self {
+        let coordinates: Vec<Coordinate> = projections.iter()
+            .map(|project| Coordinate::new(*project, &moves))
+            .collect();
+        let size = coordinates.iter().map(Coordinate::size).product();
+
+        let mut phase = Phase { moves, coordinates, distances: vec![u8::MAX; size] };
+        let mut queue = VecDeque::new();
+        for goal in goals {
+            let idx = phase.combine(&goal);
+            if phase.distances[idx] == u8::MAX {
+                phase.distances[idx] = 0;
+                queue.push_back(idx);
+            }
+        }
+
+        while let Some(idx) = queue.pop_front() {
+            let depth = phase.distances[idx];
+            for m in 0..phase.moves.len() {
+                let next = phase.next(idx, m);
+                if phase.distances[next] == u8::MAX {
+                    phase.distances[next] = depth + 1;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        phase
+    }
+
+    fn combine(&self, ids: &[usize]) -> usize {
+        self.coordinates.iter()
+            .zip(ids)
+            .fold(0, |acc, (coordinate, id)| acc * coordinate.size() + id)
+    }
+
+    /// Returns the index reached by applying move m to the state at idx.
+    fn next(&self, mut idx: usize, m: usize) -> usize {
+        let mut ids = vec![0; self.coordinates.len()];
+        for (i, coordinate) in self.coordinates.iter().enumerate().rev() {
+            ids[i] = idx % coordinate.size();
+            idx /= coordinate.size();
+        }
+
+        let num_moves = self.moves.len();
+        let ids: Vec<usize> = self.coordinates.iter()
+            .zip(ids)
+            .map(|(coordinate, id)| coordinate.moves[id * num_moves + m] as usize)
+            .collect();
+        self.combine(&ids)
+    }
+
+    /// Returns a shortest sequence of this phase's moves that takes cube to the goal, or None if
+    /// cube can't reach it with them.
+    fn solve(&self, cube: &RubiksCube) -> Option<Vec<Rotation>> {
+        let ids: Option<Vec<usize>> = self.coordinates.iter()
+            .map(|coordinate| coordinate.id(cube))
+            .collect();
+        let mut idx = self.combine(&ids?);
+
+        let mut result = Vec::new();
+        while self.distances[idx] != 0 {
+            let distance = Some(self.distances[idx]).filter(|distance| *distance != u8::MAX)?;
+            let m = (0..self.moves.len()).find(|m| self.distances[self.next(idx, *m)] < distance)?;
+            idx = self.next(idx, m);
+            result.push(self.moves[m].clone());
+        }
+
+        Some(result)
+    }
+}
+
+fn all_directions(face: &'static Color) -> [Rotation; 3] {
+    [Direction::Clockwise, Direction::CounterClockwise, Direction::Double]
+        .map(|direction| Rotation { face, direction })
+}
+
+fn double(face: &'static Color) -> Rotation {
+    Rotation { face, direction: Direction::Double }
+}
+
+// The edges whose colors include neither white nor yellow, which are in the last four edge
+// positions when solved.
+fn is_middle_edge(piece: &u8) -> bool {
+    *piece >= 8
+}
+
+fn edge_orientations(cube: &RubiksCube) -> Vec<u8> {
+    cube.edge_orientations().to_vec()
+}
+
+fn corner_orientations(cube: &RubiksCube) -> Vec<u8> {
+    cube.corner_orientations().to_vec()
+}
+
+fn middle_edge_positions(cube: &RubiksCube) -> Vec<u8> {
+    cube.edge_permutation().iter().map(|piece| is_middle_edge(piece) as u8).collect()
+}
+
+fn corner_permutation(cube: &RubiksCube) -> Vec<u8> {
+    cube.corner_permutation().to_vec()
+}
+
+/// Marks the positions holding the red and orange edges that also have white or yellow, which
+/// double turns keep between the blue and green faces.
+fn red_orange_edge_positions(cube: &RubiksCube) -> Vec<u8> {
+    let positions = edge_positions();
+    let is_red_orange = |piece: &u8| {
+        let colors = positions[*piece as usize];
+        colors.contains(&&RED) || colors.contains(&&ORANGE)
+    };
+    cube.edge_permutation().iter()
+        .map(|piece| (!is_middle_edge(piece) && is_red_orange(piece)) as u8)
+        .collect()
+}
+
+fn edge_permutation(cube: &RubiksCube) -> Vec<u8> {
+    cube.edge_permutation().to_vec()
+}
+
+fn build_phases() -> Vec<Phase> {
+    let phase_1_moves: Vec<Rotation> = ALL_COLORS.into_iter().flat_map(all_directions).collect();
+    let phase_2_moves: Vec<Rotation> = [&WHITE, &YELLOW, &BLUE, &GREEN].into_iter()
+        .flat_map(all_directions)
+        .chain([double(&RED), double(&ORANGE)])
+        .collect();
+    let phase_3_moves: Vec<Rotation> = [&WHITE, &YELLOW].into_iter()
+        .flat_map(all_directions)
+        .chain([&BLUE, &GREEN, &RED, &ORANGE].map(double))
+        .collect();
+    let phase_4_moves: Vec<Rotation> = ALL_COLORS.map(double).to_vec();
+
+    // Every coordinate numbers the solved cube's value 0, so the goals of the first two phases
+    // and the last are only the solved values.
+    let phase_1 = Phase::new(phase_1_moves, &[edge_orientations], vec![vec![0]]);
+    let phase_2 = Phase::new(phase_2_moves, &[corner_orientations, middle_edge_positions], vec![vec![0, 0]]);
+
+    // Corners are in G3 when their permutation is one of the 96 that double turns can reach.
+    let corners_in_g3 = Coordinate::new(corner_permutation, &phase_4_moves);
+    let phase_3 = {
+        let corners = Coordinate::new(corner_permutation, &phase_3_moves);
+        let goals = corners_in_g3.ids.keys()
+            .map(|permutation| vec![corners.ids[permutation], 0])
+            .collect();
+        Phase::new(phase_3_moves, &[corner_permutation, red_orange_edge_positions], goals)
+    };
+
+    let phase_4 = Phase::new(phase_4_moves, &[corner_permutation, edge_permutation], vec![vec![0, 0]]);
+
+    vec![phase_1, phase_2, phase_3, phase_4]
+}
+
+impl <'a> RubiksCube<'a> {
+    /// Solves the cube with Thistlethwaite's algorithm. Solutions are usually 25 to 40 moves,
+    /// counting double turns as one. The lookup tables are built on the first call, which takes a
+    /// few seconds. Returns None if the cube can't be solved.
+    pub fn solve_thistlethwaite(&self) -> Option<Vec<Rotation>> {
+        let phases = PHASES.get_or_init(build_phases);
+        let mut cube = self.clone();
+        let mut result = Vec::new();
+
+        for phase in phases {
+            let moves = phase.solve(&cube)?;
+            moves.iter().for_each(|rotation| cube.apply(rotation));
+            result.extend(moves);
+        }
+
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::rubiks_cube::verify;
+
+    fn scrambled(seed: u64) -> RubiksCube<'static> {
+        let mut cube = RubiksCube::solved();
+        cube.scramble_seeded(seed, 40);
+        cube
+    }
+
+    fn in_g1(cube: &RubiksCube) -> bool {
+        cube.edge_orientations() == [0; 12]
+    }
+
+    fn in_g2(cube: &RubiksCube) -> bool {
+        in_g1(cube)
+            && cube.corner_orientations() == [0; 8]
+            && cube.edge_permutation()[8..].iter().all(is_middle_edge)
+    }
+
+    /// A state is in G3 if double turns alone solve it.
+    fn in_g3(cube: &RubiksCube) -> bool {
+        let phases = PHASES.get_or_init(build_phases);
+        phases[3].solve(cube).is_some_and(|moves| verify(cube, &moves))
+    }
+
+    #[test]
+    fn solve_thistlethwaite_solves_random_scrambles() {
+        for seed in 0..5 {
+            let cube = scrambled(seed);
+            assert!(verify(&cube, &cube.solve_thistlethwaite().unwrap()));
+        }
+    }
+
+    #[test]
+    fn each_phase_reaches_the_next_subgroup() {
+        let phases = PHASES.get_or_init(build_phases);
+        let checks: [fn(&RubiksCube) -> bool; 4] = [in_g1, in_g2, in_g3, |cube| cube.is_solved()];
+
+        for seed in 0..5 {
+            let mut cube = scrambled(seed);
+            for (phase, in_goal) in phases.iter().zip(checks) {
+                let moves = phase.solve(&cube).unwrap();
+                assert!(moves.iter().all(|rotation| phase.moves.contains(rotation)));
+
+                moves.iter().for_each(|rotation| cube.apply(rotation));
+                assert!(in_goal(&cube));
+            }
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use rubiks_solver::model::rotation::parse_sequence;
+use rubiks_solver::model::rubiks_cube::RubiksCube;
+
+fn cli(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_cli")).args(args).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn assert_solves(cube: &RubiksCube, output: &str) {
+    let mut solved = cube.clone();
+    parse_sequence(output.trim()).unwrap().iter().for_each(|rotation| solved.turn(rotation));
+    assert!(solved.is_solved());
+}
+
+#[test]
+fn solve_prints_a_solution() {
+    let cube = RubiksCube::from_scramble("w r b' o2 g y'").unwrap();
+    assert_solves(&cube, &cli(&["solve", &cube.to_facelet_string()]));
+}
+
+#[test]
+fn solve_reads_moves_from_stdin() {
+    let scramble = "w r b' o2 g y', r2";
+    let mut child = Command::new(env!("CARGO_BIN_EXE_cli"))
+        .args(["solve", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(scramble.as_bytes()).unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_solves(&RubiksCube::from_scramble(scramble).unwrap(), &String::from_utf8(output.stdout).unwrap());
+}